@@ -0,0 +1,134 @@
+//! Measures the cost of a tick near the end of a game on a 300×100 field, where the snake covers
+//! almost every cell.
+//!
+//! Compares the occupancy grid against scanning the segments, which the game did before.
+//!
+//! Run with `cargo bench --bench endgame`.
+
+use rustsnake::cyclic_buffer::CyclicBuffer;
+use rustsnake::occupancy::OccupancyGrid;
+use rustsnake::random::{self, RandomNumberEngineExt};
+use rustsnake::types::{Dimensions, Position};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const FIELD: Dimensions = Dimensions { x: 300, y: 100 };
+/// Number of inner cells that are not covered by the snake.
+const FREE_CELLS: usize = 50;
+const TICKS: u32 = 100_000;
+const SCAN_TICKS: u32 = 3;
+
+/// Builds a Hamiltonian cycle through the inner cells of the field, so the snake can move along
+/// it forever without colliding.
+///
+/// It goes down the first column, then meanders through the remaining columns from the bottom
+/// row up to the second row and returns along the top row.
+fn hamiltonian_cycle() -> Vec<Position> {
+    let (width, height) = (FIELD.x - 2, FIELD.y - 2);
+    assert!(width % 2 == 0 && height % 2 == 0);
+    let inner = |x: usize, y: usize| Position { x: x + 1, y: y + 1 };
+    let mut cycle: Vec<Position> = (0..height).map(|y| inner(0, y)).collect();
+    for (i, y) in (1..height).rev().enumerate() {
+        match i % 2 {
+            0 => cycle.extend((1..width).map(|x| inner(x, y))),
+            _ => cycle.extend((1..width).rev().map(|x| inner(x, y))),
+        }
+    }
+    cycle.extend((1..width).rev().map(|x| inner(x, 0)));
+    cycle
+}
+
+/// Snake of maximal length but `FREE_CELLS` moving along the cycle.
+struct Endgame {
+    cycle: Vec<Position>,
+    head_index: usize,
+    segments: CyclicBuffer<Position>,
+}
+
+impl Endgame {
+    fn new() -> Self {
+        let cycle = hamiltonian_cycle();
+        let length = cycle.len() - FREE_CELLS;
+        let mut segments = CyclicBuffer::new(length);
+        segments.extend(cycle[..length].iter());
+        Self {
+            cycle,
+            head_index: length - 1,
+            segments,
+        }
+    }
+
+    /// Moves the head along the cycle and returns the new head after removing the tail.
+    fn advance(&mut self) -> Position {
+        self.head_index = (self.head_index + 1) % self.cycle.len();
+        self.segments.pop();
+        self.cycle[self.head_index]
+    }
+}
+
+/// Checks for collisions and places food by scanning all segments.
+fn tick_scanning(game: &mut Endgame, rng: &mut impl RandomNumberEngineExt) -> Position {
+    let head = game.advance();
+    let collided = game.segments.iter().any(|segment| *segment == head);
+    game.segments.push(head);
+    let mut free_fields = Vec::new();
+    for y in 1..FIELD.y - 1 {
+        for x in 1..FIELD.x - 1 {
+            let position = Position { x, y };
+            if !game.segments.iter().any(|segment| *segment == position) {
+                free_fields.push(position);
+            }
+        }
+    }
+    assert!(!collided);
+    *rng.choose(&free_fields).unwrap()
+}
+
+/// Checks for collisions and places food using the occupancy grid.
+fn tick_occupancy(
+    game: &mut Endgame,
+    occupancy: &mut OccupancyGrid,
+    rng: &mut impl RandomNumberEngineExt,
+) -> Position {
+    let tail = *game.segments.front().unwrap();
+    let head = game.advance();
+    occupancy.vacate(&tail);
+    let collided = !occupancy.occupy(&head);
+    game.segments.push(head);
+    assert!(!collided);
+    *occupancy.choose_free(rng).unwrap()
+}
+
+fn report(name: &str, elapsed: Duration, ticks: u32) {
+    println!("{:<10} {:>12.3?} per tick", name, elapsed / ticks);
+}
+
+fn main() {
+    let mut rng = random::PCG32Fast::new(None);
+
+    let mut game = Endgame::new();
+    let mut occupancy = OccupancyGrid::new(&FIELD);
+    for x in 0..FIELD.x {
+        for y in 0..FIELD.y {
+            if x == 0 || y == 0 || x == FIELD.x - 1 || y == FIELD.y - 1 {
+                occupancy.occupy(&Position { x, y });
+            }
+        }
+    }
+    game.segments.iter().for_each(|segment| {
+        occupancy.occupy(segment);
+    });
+    assert_eq!(occupancy.free_count(), FREE_CELLS);
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        black_box(tick_occupancy(&mut game, &mut occupancy, &mut rng));
+    }
+    report("occupancy", start.elapsed(), TICKS);
+
+    let mut game = Endgame::new();
+    let start = Instant::now();
+    for _ in 0..SCAN_TICKS {
+        black_box(tick_scanning(&mut game, &mut rng));
+    }
+    report("scanning", start.elapsed(), SCAN_TICKS);
+}
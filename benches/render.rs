@@ -0,0 +1,96 @@
+//! Measures the bytes written per frame and the time to encode them on a 120×40 terminal for
+//! typical frames, with and without the optional control functions.
+//!
+//! Run with `cargo bench --bench render`.
+
+use rustsnake::frame_buffer::draw::{Alignment, BorderStyle};
+use rustsnake::frame_buffer::{Color, FrameBuffer, Pixel};
+use rustsnake::terminal::Capabilities;
+use rustsnake::types::{Dimensions, Position, Rect};
+use std::time::Instant;
+
+const DIMENSIONS: Dimensions = Dimensions { x: 120, y: 40 };
+const FRAMES: usize = 1000;
+const SNAKE_LENGTH: usize = 60;
+
+/// Draws the border, the status line and a snake whose head is `tick` cells along the field.
+fn draw_game(frame_buffer: &mut FrameBuffer, tick: usize) {
+    let field = Rect::new(
+        Position::new(0, 0),
+        Dimensions::new(DIMENSIONS.x, DIMENSIONS.y - 1),
+    );
+    let mut canvas = frame_buffer.canvas();
+    canvas.fill(&field, Pixel::new('█', Color::Blue));
+    let inner = Rect::new(
+        Position::new(1, 1),
+        Dimensions::new(field.dimensions.x - 2, field.dimensions.y - 2),
+    );
+    canvas.fill(&inner, Pixel::new(' ', Color::Default));
+    let status = Rect::new(
+        Position::new(1, DIMENSIONS.y - 1),
+        Dimensions::new(DIMENSIONS.x - 2, 1),
+    );
+    let score = format!("Score: {}", tick / 10 * 100);
+    canvas.draw_text(&status, &score, Color::Red, Alignment::Left);
+    canvas.draw_text(&status, "Speed: 5", Color::Red, Alignment::Right);
+    // The snake meanders through the rows of the inner field.
+    let cells = inner.area();
+    for segment in tick.saturating_sub(SNAKE_LENGTH)..tick {
+        let index = segment % cells;
+        let (row, column) = (index / inner.dimensions.x, index % inner.dimensions.x);
+        let column = match row % 2 {
+            0 => column,
+            _ => inner.dimensions.x - 1 - column,
+        };
+        canvas.put(inner.origin + Position::new(column, row), '◉', Color::Green);
+    }
+}
+
+/// Alternates between the game and a pause dialog drawn over it.
+fn draw_pause(frame_buffer: &mut FrameBuffer, tick: usize) {
+    draw_game(frame_buffer, 1000);
+    if tick.is_multiple_of(2) {
+        let mut canvas = frame_buffer.canvas();
+        let rect = canvas.draw_dialog("Paused", "Press p to continue", Color::White);
+        canvas.draw_box(&rect, &BorderStyle::DOUBLE, Color::White);
+    }
+}
+
+fn run(name: &str, capabilities: Capabilities, draw: fn(&mut FrameBuffer, usize)) {
+    let mut frame_buffer = FrameBuffer::with_capabilities(&DIMENSIONS, capabilities);
+    let mut output = Vec::new();
+    draw(&mut frame_buffer, 0);
+    frame_buffer.swap_buffers_to(&mut output).unwrap();
+    let first_frame = output.len();
+    output.clear();
+    let start = Instant::now();
+    for tick in 1..=FRAMES {
+        draw(&mut frame_buffer, tick);
+        frame_buffer.swap_buffers_to(&mut output).unwrap();
+    }
+    let elapsed = start.elapsed();
+    let capabilities = match capabilities.erase_character || capabilities.repeat_character {
+        true => "ECH+REP",
+        false => "basic",
+    };
+    println!(
+        "{:<6} {:<8} first frame {:>6} B, {:>6} B per frame, {:>10.3?} per frame",
+        name,
+        capabilities,
+        first_frame,
+        output.len() / FRAMES,
+        elapsed / FRAMES as u32
+    );
+}
+
+fn main() {
+    let all = Capabilities {
+        erase_character: true,
+        repeat_character: true,
+        synchronized_output: false,
+    };
+    for capabilities in [Capabilities::default(), all] {
+        run("game", capabilities, draw_game);
+        run("pause", capabilities, draw_pause);
+    }
+}
@@ -0,0 +1,57 @@
+//! Compares the throughput of the random number engines.
+//!
+//! Run with `cargo bench --bench rng`.
+
+use rustsnake::random::{self, EngineKind, RandomNumberEngineExt};
+use std::hint::black_box;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 10_000_000;
+
+/// Measures the nanoseconds per generated value of a single operation on the engine.
+fn measure<R: RandomNumberEngineExt>(rng: &mut R, operation: impl Fn(&mut R) -> u64) -> f64 {
+    // Warm up caches and the branch predictor before measuring.
+    for _ in 0..ITERATIONS / 10 {
+        black_box(operation(rng));
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(operation(rng));
+    }
+    start.elapsed().as_secs_f64() * 1e9 / ITERATIONS as f64
+}
+
+fn bench<R: RandomNumberEngineExt>(kind: EngineKind, mut rng: R) {
+    let next_u32 = measure(&mut rng, |rng| rng.next_u32() as u64);
+    let next_u64 = measure(&mut rng, |rng| rng.next_u64());
+    let gen_below = measure(&mut rng, |rng| rng.gen_below(black_box(1000u64)));
+    let gen_f64 = measure(&mut rng, |rng| rng.gen_f64().to_bits());
+    println!(
+        "{:<20} {:>8.2}ns {:>8.2}ns {:>8.2}ns {:>8.2}ns",
+        kind.name(),
+        next_u32,
+        next_u64,
+        gen_below,
+        gen_f64
+    );
+}
+
+fn main() {
+    println!(
+        "{:<20} {:>10} {:>10} {:>10} {:>10}",
+        "engine", "next_u32", "next_u64", "gen_below", "gen_f64"
+    );
+    for kind in EngineKind::ALL {
+        match kind {
+            EngineKind::PCG32Fast => bench(kind, random::PCG32Fast::new(None)),
+            EngineKind::PCG32FastRxsMXs => bench(kind, random::PCG32FastRxsMXs::new(None)),
+            EngineKind::PCG32 => bench(kind, random::PCG32::new(None, None)),
+            EngineKind::PCG64 => bench(kind, random::PCG64::new(None, None)),
+            EngineKind::PCG64Fast => bench(kind, random::PCG64Fast::new(None)),
+            EngineKind::SplitMix64 => bench(kind, random::SplitMix64::new(None)),
+            EngineKind::Xoshiro256StarStar => bench(kind, random::Xoshiro256StarStar::new(None)),
+            EngineKind::Xoroshiro128Plus => bench(kind, random::Xoroshiro128Plus::new(None)),
+            EngineKind::WyRand => bench(kind, random::WyRand::new(None)),
+        }
+    }
+}
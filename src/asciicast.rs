@@ -0,0 +1,120 @@
+//! Records terminal output in the asciicast v2 format of asciinema.
+//!
+//! A recording is a JSON header line followed by one line per chunk of output, each holding the
+//! time since the start of the recording. The times are passed in by the caller rather than taken
+//! from a clock, so recordings can be produced offline as fast as the frames can be rendered.
+
+use crate::types::Dimensions;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::time::Duration;
+
+pub struct Recorder<W: Write> {
+    writer: W,
+    line: String,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Starts a recording of a terminal with the given dimensions by writing the header.
+    ///
+    /// The timestamp is the start of the recording in seconds since the Unix epoch, if known.
+    pub fn new(writer: W, dimensions: &Dimensions, timestamp: Option<u64>) -> io::Result<Self> {
+        let mut recorder = Self {
+            writer,
+            line: String::new(),
+        };
+        write!(
+            recorder.line,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}",
+            dimensions.x, dimensions.y
+        )
+        .unwrap();
+        if let Some(timestamp) = timestamp {
+            write!(recorder.line, ", \"timestamp\": {}", timestamp).unwrap();
+        }
+        recorder.line.push_str("}\n");
+        recorder.write_line()?;
+        Ok(recorder)
+    }
+
+    fn write_line(&mut self) -> io::Result<()> {
+        self.writer.write_all(self.line.as_bytes())?;
+        self.line.clear();
+        Ok(())
+    }
+
+    /// Records output written to the terminal at the given time since the start. Empty output
+    /// is left out.
+    pub fn output(&mut self, time: Duration, bytes: &[u8]) -> io::Result<()> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        write!(self.line, "[{:.6}, \"o\", ", time.as_secs_f64()).unwrap();
+        push_json_string(&mut self.line, &String::from_utf8_lossy(bytes));
+        self.line.push_str("]\n");
+        self.write_line()
+    }
+
+    /// Flushes the recording and gets the writer back.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Appends a string as a quoted JSON string.
+fn push_json_string(output: &mut String, text: &str) {
+    output.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                write!(output, "\\u{:04x}", character as u32).unwrap();
+            }
+            character => output.push(character),
+        }
+    }
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json_string(text: &str) -> String {
+        let mut output = String::new();
+        push_json_string(&mut output, text);
+        output
+    }
+
+    #[test]
+    fn json_strings() {
+        assert_eq!(json_string("abc"), "\"abc\"");
+        assert_eq!(json_string("\x1b[1;2H中"), "\"\\u001b[1;2H中\"");
+        assert_eq!(json_string("\"\\\r\n\t\x7f"), "\"\\\"\\\\\\r\\n\\t\x7f\"");
+    }
+
+    #[test]
+    fn recording() {
+        let mut recorder =
+            Recorder::new(Vec::new(), &Dimensions::new(80, 24), Some(1700000000)).unwrap();
+        recorder
+            .output(Duration::from_millis(1500), b"\x1b[1;1Hx")
+            .unwrap();
+        recorder.output(Duration::from_secs(2), b"").unwrap();
+        recorder
+            .output(Duration::from_micros(2_000_001), b"\r\n")
+            .unwrap();
+        let recording = String::from_utf8(recorder.finish().unwrap()).unwrap();
+        assert_eq!(
+            recording,
+            "{\"version\": 2, \"width\": 80, \"height\": 24, \"timestamp\": 1700000000}\n\
+             [1.500000, \"o\", \"\\u001b[1;1Hx\"]\n\
+             [2.000001, \"o\", \"\\r\\n\"]\n"
+        );
+    }
+}
@@ -1,105 +1,442 @@
+pub mod array;
+
+pub use array::CyclicArray;
+
+/// Determines what happens when an element is added to a full [`CyclicBuffer`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GrowthPolicy {
+    /// The capacity never changes implicitly: `push` fails and `force_push` overwrites.
+    #[default]
+    Fixed,
+    /// The capacity doubles.
+    Double,
+    /// The capacity grows by the given number of elements, but at least by one.
+    Linear(usize),
+}
+
 pub struct CyclicBuffer<T: Default + Clone> {
     segments: Vec<T>,
     head_index: usize,
-    beyond_tail_index: usize,
-    empty: bool,
+    len: usize,
+    growth_policy: GrowthPolicy,
 }
 
 impl<T: Default + Clone> CyclicBuffer<T> {
     pub fn new(max_size: usize) -> Self {
+        Self::with_growth_policy(max_size, GrowthPolicy::Fixed)
+    }
+
+    pub fn with_growth_policy(capacity: usize, growth_policy: GrowthPolicy) -> Self {
         Self {
-            segments: vec![T::default(); max_size],
+            segments: vec![T::default(); capacity],
             head_index: 0,
-            beyond_tail_index: 0,
-            empty: true,
+            len: 0,
+            growth_policy,
         }
     }
 
+    pub fn growth_policy(&self) -> GrowthPolicy {
+        self.growth_policy
+    }
+
+    pub fn set_growth_policy(&mut self, growth_policy: GrowthPolicy) {
+        self.growth_policy = growth_policy;
+    }
+
     pub fn count(&self) -> usize {
-        if self.empty {
-            0
-        } else if self.beyond_tail_index > self.head_index {
-            self.beyond_tail_index - self.head_index
-        } else {
-            self.beyond_tail_index + self.capacity() - self.head_index
-        }
+        self.len
     }
 
     pub fn empty(&self) -> bool {
-        self.empty
+        self.len == 0
     }
 
     pub fn full(&self) -> bool {
-        self.count() == self.capacity()
+        self.len == self.capacity()
     }
 
     pub fn capacity(&self) -> usize {
         self.segments.len()
     }
 
-    fn increment(&self, index: usize) -> usize {
-        (index + 1) % self.capacity()
+    /// Maps a position relative to the front to an index into `segments`.
+    fn physical_index(&self, position: usize) -> usize {
+        let index = self.head_index + position;
+        if index >= self.capacity() {
+            index - self.capacity()
+        } else {
+            index
+        }
+    }
+
+    /// Grows the capacity according to the growth policy if the buffer is full.
+    ///
+    /// Returns whether there is room for another element.
+    fn grow_if_full(&mut self) -> bool {
+        if !self.full() {
+            return true;
+        }
+        let capacity = self.capacity();
+        let new_capacity = match self.growth_policy {
+            GrowthPolicy::Fixed => return false,
+            GrowthPolicy::Double => std::cmp::max(capacity * 2, 1),
+            GrowthPolicy::Linear(step) => capacity + std::cmp::max(step, 1),
+        };
+        self.resize_capacity(new_capacity);
+        true
     }
 
+    /// Appends an element to the back, failing if the buffer is full and cannot grow.
     pub fn push(&mut self, value: T) -> bool {
-        if self.beyond_tail_index == self.head_index && !self.empty {
+        if !self.grow_if_full() {
             return false;
         }
-        let new_tail_index = self.increment(self.beyond_tail_index);
-        self.segments[self.beyond_tail_index] = value;
-        self.beyond_tail_index = new_tail_index;
-        self.empty = false;
+        let tail_index = self.physical_index(self.len);
+        self.segments[tail_index] = value;
+        self.len += 1;
         true
     }
 
+    /// Appends an element to the back, overwriting the front element if the buffer is full and
+    /// cannot grow.
     pub fn force_push(&mut self, value: T) {
-        if self.beyond_tail_index == self.head_index && !self.empty {
-            self.head_index = self.increment(self.head_index);
+        if self.grow_if_full() {
+            self.push(value);
+        } else if self.capacity() > 0 {
+            self.pop();
+            self.push(value);
         }
-        let new_tail_index = self.increment(self.beyond_tail_index);
-        self.segments[self.beyond_tail_index] = value;
-        self.beyond_tail_index = new_tail_index;
-        self.empty = false;
     }
 
+    /// Prepends an element to the front, failing if the buffer is full and cannot grow.
+    pub fn push_front(&mut self, value: T) -> bool {
+        if !self.grow_if_full() {
+            return false;
+        }
+        self.head_index = self.physical_index(self.capacity() - 1);
+        self.segments[self.head_index] = value;
+        self.len += 1;
+        true
+    }
+
+    /// Removes the front element.
     pub fn pop(&mut self) -> Option<T> {
-        if self.empty {
+        if self.empty() {
             return None;
         }
         let head_index = self.head_index;
-        self.head_index = self.increment(self.head_index);
-        self.empty = self.head_index == self.beyond_tail_index;
+        self.head_index = self.physical_index(1);
+        self.len -= 1;
         Some(std::mem::take(&mut self.segments[head_index]))
     }
 
+    /// Removes the back element.
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.empty() {
+            return None;
+        }
+        self.len -= 1;
+        let tail_index = self.physical_index(self.len);
+        Some(std::mem::take(&mut self.segments[tail_index]))
+    }
+
+    /// Gets the element at `position` counted from the front.
+    pub fn get(&self, position: usize) -> Option<&T> {
+        (position < self.len).then(|| &self.segments[self.physical_index(position)])
+    }
+
+    /// Gets the element at `position` counted from the front.
+    pub fn get_mut(&mut self, position: usize) -> Option<&mut T> {
+        if position >= self.len {
+            return None;
+        }
+        let index = self.physical_index(position);
+        Some(&mut self.segments[index])
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.wrapping_sub(1))
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.wrapping_sub(1))
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+        self.head_index = 0;
+    }
+
+    /// Removes the elements in `range` and returns them in order.
+    ///
+    /// The elements are removed immediately, even if the returned iterator is not consumed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn drain(&mut self, range: impl std::ops::RangeBounds<usize>) -> std::vec::IntoIter<T> {
+        use std::ops::Bound;
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        assert!(
+            start <= end && end <= self.len,
+            "drain range {}..{} out of bounds for length {}",
+            start,
+            end,
+            self.len
+        );
+        let drained: Vec<T> = (start..end)
+            .map(|position| std::mem::take(&mut self[position]))
+            .collect();
+        // Close the gap by moving the shorter side.
+        let removed = end - start;
+        if start < self.len - end {
+            for position in (0..start).rev() {
+                self[position + removed] = std::mem::take(&mut self[position]);
+            }
+            self.head_index = self.physical_index(removed);
+        } else {
+            for position in end..self.len {
+                self[position - removed] = std::mem::take(&mut self[position]);
+            }
+        }
+        self.len -= removed;
+        drained.into_iter()
+    }
+
+    /// Changes the capacity while preserving the order of the elements.
+    ///
+    /// When shrinking below the number of elements, the oldest ones at the front are dropped.
+    pub fn resize_capacity(&mut self, capacity: usize) {
+        let dropped = self.len.saturating_sub(capacity);
+        let mut segments = Vec::with_capacity(capacity);
+        segments.extend(self.drain(..).skip(dropped));
+        self.len = segments.len();
+        segments.resize(capacity, T::default());
+        self.segments = segments;
+        self.head_index = 0;
+    }
+
+    /// Makes sure that at least `additional` more elements fit without growing.
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len + additional;
+        if required > self.capacity() {
+            self.resize_capacity(required);
+        }
+    }
+
+    /// Reduces the capacity to the number of elements.
+    pub fn shrink_to_fit(&mut self) {
+        self.resize_capacity(self.len);
+    }
+
+    /// Rearranges the elements so that they are stored in a single slice, which is returned.
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        self.segments.rotate_left(self.head_index);
+        self.head_index = 0;
+        &mut self.segments[..self.len]
+    }
+
+    /// Gets the elements in order as up to two contiguous slices.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let end = self.head_index + self.len;
+        if end <= self.capacity() {
+            (&self.segments[self.head_index..end], &[])
+        } else {
+            let (wrapped, head) = self.segments.split_at(self.head_index);
+            (head, &wrapped[..end - self.capacity()])
+        }
+    }
+
+    /// Gets the elements in order as up to two contiguous mutable slices.
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let end = self.head_index + self.len;
+        let capacity = self.capacity();
+        if end <= capacity {
+            (&mut self.segments[self.head_index..end], &mut [])
+        } else {
+            let (wrapped, head) = self.segments.split_at_mut(self.head_index);
+            (head, &mut wrapped[..end - capacity])
+        }
+    }
+
     pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
         Iter {
-            buffer: self,
-            position: 0,
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (front, back) = self.as_mut_slices();
+        IterMut {
+            front: front.iter_mut(),
+            back: back.iter_mut(),
         }
     }
 }
 
-#[derive(Clone)]
-pub struct Iter<'a, T: Default + Clone> {
-    buffer: &'a CyclicBuffer<T>,
-    position: usize,
+impl<T: Default + Clone> std::ops::Index<usize> for CyclicBuffer<T> {
+    type Output = T;
+
+    fn index(&self, position: usize) -> &Self::Output {
+        let len = self.len;
+        self.get(position)
+            .unwrap_or_else(|| panic!("position {} out of bounds for length {}", position, len))
+    }
+}
+
+impl<T: Default + Clone> std::ops::IndexMut<usize> for CyclicBuffer<T> {
+    fn index_mut(&mut self, position: usize) -> &mut Self::Output {
+        let len = self.len;
+        self.get_mut(position)
+            .unwrap_or_else(|| panic!("position {} out of bounds for length {}", position, len))
+    }
+}
+
+/// Appends the elements to the back like [`CyclicBuffer::force_push`].
+impl<T: Default + Clone> Extend<T> for CyclicBuffer<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|value| self.force_push(value));
+    }
+}
+
+impl<'a, T: Default + Clone + 'a> Extend<&'a T> for CyclicBuffer<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().cloned());
+    }
+}
+
+impl<T: Default + Clone> IntoIterator for CyclicBuffer<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { buffer: self }
+    }
 }
 
-impl<'a, T: Default + Clone> core::iter::Iterator for Iter<'a, T> {
+impl<'a, T: Default + Clone> IntoIterator for &'a CyclicBuffer<T> {
     type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.position == self.buffer.count() {
-            return None;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T: Default + Clone> IntoIterator for &'a mut CyclicBuffer<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+pub struct Iter<'a, T> {
+    front: std::slice::Iter<'a, T>,
+    back: std::slice::Iter<'a, T>,
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            front: self.front.clone(),
+            back: self.back.clone(),
         }
-        let cyclic_position = (self.buffer.head_index + self.position) % self.buffer.capacity();
-        let segment = &self.buffer.segments[cyclic_position];
-        self.position += 1;
-        Some(segment)
     }
 }
 
+impl<'a, T> core::iter::Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> core::iter::DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> core::iter::ExactSizeIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    front: std::slice::IterMut<'a, T>,
+    back: std::slice::IterMut<'a, T>,
+}
+
+impl<'a, T> core::iter::Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> core::iter::DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> core::iter::ExactSizeIterator for IterMut<'_, T> {}
+
+pub struct IntoIter<T: Default + Clone> {
+    buffer: CyclicBuffer<T>,
+}
+
+impl<T: Default + Clone> core::iter::Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buffer.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.buffer.count(), Some(self.buffer.count()))
+    }
+}
+
+impl<T: Default + Clone> core::iter::DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.buffer.pop_back()
+    }
+}
+
+impl<T: Default + Clone> core::iter::ExactSizeIterator for IntoIter<T> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -168,4 +505,289 @@ mod tests {
         assert_eq!(*iter.next().unwrap_or(&0), 3);
         assert!(iter.next().is_none());
     }
+
+    #[test]
+    fn front_and_back() {
+        let mut buf = CyclicBuffer::<u32>::new(3);
+        assert_eq!(buf.front(), None);
+        assert_eq!(buf.back(), None);
+        (1..=4).for_each(|value| buf.force_push(value));
+        assert_eq!(buf.front(), Some(&2));
+        assert_eq!(buf.back(), Some(&4));
+        *buf.front_mut().unwrap() = 5;
+        *buf.back_mut().unwrap() = 6;
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [5, 3, 6]);
+    }
+
+    #[test]
+    fn push_front_and_pop_back() {
+        let mut buf = CyclicBuffer::<u32>::new(3);
+        assert!(buf.push_front(1));
+        assert!(buf.push_front(2));
+        assert!(buf.push(3));
+        assert!(!buf.push_front(4));
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [2, 1, 3]);
+        assert_eq!(buf.pop_back(), Some(3));
+        assert_eq!(buf.pop_back(), Some(1));
+        assert_eq!(buf.pop_back(), Some(2));
+        assert_eq!(buf.pop_back(), None);
+    }
+
+    #[test]
+    fn index() {
+        let mut buf = CyclicBuffer::<u32>::new(3);
+        buf.extend([1, 2, 3, 4]);
+        assert_eq!((buf[0], buf[1], buf[2]), (2, 3, 4));
+        buf[1] = 5;
+        assert_eq!(buf.get(1), Some(&5));
+        assert_eq!(buf.get(3), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        let buf = CyclicBuffer::<u32>::new(3);
+        let _ = buf[0];
+    }
+
+    #[test]
+    fn double_ended_iter() {
+        let mut buf = CyclicBuffer::<u32>::new(4);
+        buf.extend([1, 2, 3, 4, 5, 6]);
+        let mut iter = buf.iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next_back(), Some(&6));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.rev().copied().collect::<Vec<_>>(), [5, 4]);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut buf = CyclicBuffer::<u32>::new(3);
+        buf.extend([1, 2, 3, 4]);
+        buf.iter_mut().for_each(|value| *value *= 10);
+        for value in &mut buf {
+            *value += 1;
+        }
+        assert_eq!(
+            (&buf).into_iter().copied().collect::<Vec<_>>(),
+            [21, 31, 41]
+        );
+        assert_eq!(buf.into_iter().rev().collect::<Vec<_>>(), [41, 31, 21]);
+    }
+
+    #[test]
+    fn as_slices() {
+        let mut buf = CyclicBuffer::<u32>::new(4);
+        buf.extend([1, 2, 3]);
+        assert_eq!(buf.as_slices(), (&[1, 2, 3][..], &[][..]));
+        buf.extend([4, 5]);
+        assert_eq!(buf.as_slices(), (&[2, 3, 4][..], &[5][..]));
+        buf.as_mut_slices().1[0] = 6;
+        assert_eq!(buf.back(), Some(&6));
+    }
+
+    #[test]
+    fn drain_and_clear() {
+        let mut buf = CyclicBuffer::<u32>::new(5);
+        buf.extend(1..=7);
+        assert_eq!(buf.drain(1..3).collect::<Vec<_>>(), [4, 5]);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [3, 6, 7]);
+        assert_eq!(buf.drain(..).collect::<Vec<_>>(), [3, 6, 7]);
+        assert!(buf.empty());
+        buf.extend([1, 2]);
+        buf.clear();
+        assert!(buf.empty());
+        assert!(buf.iter().next().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn drain_out_of_bounds() {
+        let mut buf = CyclicBuffer::<u32>::new(3);
+        buf.push(1);
+        buf.drain(..2);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut buf = CyclicBuffer::<u32>::new(0);
+        assert!(buf.full());
+        assert!(!buf.push(1));
+        assert!(!buf.push_front(1));
+        buf.force_push(1);
+        assert_eq!(buf.pop(), None);
+        assert_eq!(buf.back(), None);
+    }
+
+    #[test]
+    fn growth_policy() {
+        let mut buf = CyclicBuffer::<u32>::with_growth_policy(0, GrowthPolicy::Double);
+        assert!(buf.push(1));
+        assert_eq!(buf.capacity(), 1);
+        assert!(buf.push(2));
+        assert!(buf.push_front(0));
+        assert_eq!(buf.capacity(), 4);
+        buf.set_growth_policy(GrowthPolicy::Linear(3));
+        buf.extend([3, 4]);
+        assert_eq!(buf.capacity(), 7);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+
+        buf.set_growth_policy(GrowthPolicy::Fixed);
+        buf.extend([5, 6, 7]);
+        assert_eq!(buf.capacity(), 7);
+        assert_eq!(
+            buf.iter().copied().collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5, 6, 7]
+        );
+        assert!(!buf.push(8));
+    }
+
+    #[test]
+    fn grow_while_wrapped() {
+        let mut buf = CyclicBuffer::<u32>::with_growth_policy(3, GrowthPolicy::Double);
+        buf.extend([1, 2, 3]);
+        buf.pop();
+        buf.push(4);
+        assert!(!buf.as_slices().1.is_empty());
+        buf.push(5);
+        assert_eq!(buf.capacity(), 6);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn resize_capacity_while_wrapped() {
+        let mut buf = CyclicBuffer::<u32>::new(4);
+        buf.extend(1..=6);
+        assert_eq!(buf.as_slices(), (&[3, 4][..], &[5, 6][..]));
+        buf.resize_capacity(6);
+        assert_eq!(buf.capacity(), 6);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [3, 4, 5, 6]);
+
+        buf.extend(7..=9);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [4, 5, 6, 7, 8, 9]);
+        buf.resize_capacity(3);
+        assert_eq!(buf.capacity(), 3);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [7, 8, 9]);
+        buf.resize_capacity(0);
+        assert!(buf.empty());
+    }
+
+    #[test]
+    fn reserve_and_shrink_to_fit() {
+        let mut buf = CyclicBuffer::<u32>::new(2);
+        buf.extend([1, 2, 3]);
+        buf.reserve(1);
+        assert_eq!(buf.capacity(), 3);
+        buf.reserve(1);
+        assert_eq!(buf.capacity(), 3);
+        assert!(buf.push(4));
+        buf.pop();
+        buf.shrink_to_fit();
+        assert_eq!(buf.capacity(), 2);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [3, 4]);
+    }
+
+    #[test]
+    fn make_contiguous() {
+        let mut buf = CyclicBuffer::<u32>::new(4);
+        buf.extend(1..=6);
+        assert_eq!(buf.make_contiguous(), [3, 4, 5, 6]);
+        assert_eq!(buf.as_slices(), (&[3, 4, 5, 6][..], &[][..]));
+        buf.pop();
+        buf.push(7);
+        assert_eq!(buf.make_contiguous(), [4, 5, 6, 7]);
+    }
+
+    /// Mirrors `CyclicBuffer::force_push` on a `VecDeque` limited to `capacity` elements.
+    fn force_push_bounded(
+        oracle: &mut std::collections::VecDeque<u64>,
+        capacity: usize,
+        value: u64,
+    ) {
+        if capacity == 0 {
+            return;
+        }
+        if oracle.len() == capacity {
+            oracle.pop_front();
+        }
+        oracle.push_back(value);
+    }
+
+    /// Applies random operations to a buffer and a `VecDeque` and checks that they agree.
+    #[test]
+    fn matches_vec_deque() {
+        use crate::random::{PCG32Fast, RandomNumberEngineExt, SeedFromU64};
+        use std::collections::VecDeque;
+
+        for seed in 0..200 {
+            let mut rng = PCG32Fast::seed_from_u64(seed);
+            let mut capacity = rng.gen_range(0..8);
+            let mut buf = CyclicBuffer::<u64>::new(capacity);
+            let mut oracle = VecDeque::<u64>::new();
+            for _ in 0..200 {
+                let value = rng.next_u64();
+                match rng.gen_below(11) {
+                    0 => {
+                        let pushed = buf.push(value);
+                        assert_eq!(pushed, oracle.len() < capacity);
+                        if pushed {
+                            oracle.push_back(value);
+                        }
+                    }
+                    1 => {
+                        let pushed = buf.push_front(value);
+                        assert_eq!(pushed, oracle.len() < capacity);
+                        if pushed {
+                            oracle.push_front(value);
+                        }
+                    }
+                    2 => {
+                        buf.force_push(value);
+                        force_push_bounded(&mut oracle, capacity, value);
+                    }
+                    3 => assert_eq!(buf.pop(), oracle.pop_front()),
+                    4 => assert_eq!(buf.pop_back(), oracle.pop_back()),
+                    5 => {
+                        let position = rng.gen_range(0..=oracle.len());
+                        assert_eq!(buf.get(position), oracle.get(position));
+                        if let Some(element) = buf.get_mut(position) {
+                            *element = value;
+                            oracle[position] = value;
+                        }
+                    }
+                    6 => {
+                        let start = rng.gen_range(0..=oracle.len());
+                        let end = rng.gen_range(start..=oracle.len());
+                        assert!(buf.drain(start..end).eq(oracle.drain(start..end)));
+                    }
+                    7 => {
+                        let values = [value, value >> 1, value >> 2];
+                        buf.extend(&values);
+                        values
+                            .into_iter()
+                            .for_each(|value| force_push_bounded(&mut oracle, capacity, value));
+                    }
+                    8 => {
+                        let (front, back) = buf.as_slices();
+                        assert!(front.iter().chain(back).eq(oracle.iter()));
+                    }
+                    9 => {
+                        capacity = rng.gen_range(0..8);
+                        buf.resize_capacity(capacity);
+                        while oracle.len() > capacity {
+                            oracle.pop_front();
+                        }
+                    }
+                    _ => assert!(buf.iter().rev().eq(oracle.iter().rev())),
+                }
+                assert_eq!(buf.count(), oracle.len());
+                assert_eq!(buf.front(), oracle.front());
+                assert_eq!(buf.back(), oracle.back());
+                assert_eq!(buf.iter().len(), oracle.len());
+                assert!(buf.iter().eq(oracle.iter()));
+            }
+        }
+    }
 }
@@ -0,0 +1,220 @@
+use core::mem::MaybeUninit;
+
+/// Fixed-capacity cyclic buffer stored inline without heap allocation.
+///
+/// Unlike [`super::CyclicBuffer`] the slots are not pre-filled, so `T` needs neither `Default`
+/// nor `Clone` and only live elements are dropped. It only uses `core`, so it also works in
+/// `no_std` contexts.
+pub struct CyclicArray<T, const N: usize> {
+    segments: [MaybeUninit<T>; N],
+    head_index: usize,
+    count: usize,
+}
+
+impl<T, const N: usize> CyclicArray<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            segments: [const { MaybeUninit::uninit() }; N],
+            head_index: 0,
+            count: 0,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn full(&self) -> bool {
+        self.count == N
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    fn physical_index(&self, position: usize) -> usize {
+        (self.head_index + position) % N
+    }
+
+    pub fn push(&mut self, value: T) -> bool {
+        if self.full() {
+            return false;
+        }
+        let tail_index = self.physical_index(self.count);
+        self.segments[tail_index].write(value);
+        self.count += 1;
+        true
+    }
+
+    pub fn force_push(&mut self, value: T) {
+        if N == 0 {
+            return;
+        }
+        if self.full() {
+            self.pop();
+        }
+        self.push(value);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.empty() {
+            return None;
+        }
+        let head_index = self.head_index;
+        self.head_index = self.physical_index(1);
+        self.count -= 1;
+        // SAFETY: The slot was part of the live range and is no longer, so it is read only once.
+        Some(unsafe { self.segments[head_index].assume_init_read() })
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, N> {
+        Iter {
+            buffer: self,
+            position: 0,
+        }
+    }
+}
+
+impl<T, const N: usize> Default for CyclicArray<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for CyclicArray<T, N> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+#[derive(Clone)]
+pub struct Iter<'a, T, const N: usize> {
+    buffer: &'a CyclicArray<T, N>,
+    position: usize,
+}
+
+impl<'a, T, const N: usize> core::iter::Iterator for Iter<'a, T, N> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == self.buffer.count() {
+            return None;
+        }
+        let cyclic_position = self.buffer.physical_index(self.position);
+        // SAFETY: All positions below `count` refer to initialized slots.
+        let segment = unsafe { self.buffer.segments[cyclic_position].assume_init_ref() };
+        self.position += 1;
+        Some(segment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Counts how often values sharing the same counter have been dropped.
+    struct DropCounter<'a>(&'a Cell<usize>);
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn capacity() {
+        let buf = CyclicArray::<u32, 3>::new();
+        assert_eq!(buf.capacity(), 3);
+    }
+
+    #[test]
+    fn empty() {
+        let mut buf = CyclicArray::<u32, 3>::new();
+        assert!(buf.empty());
+        assert_eq!(buf.count(), 0);
+        assert_eq!(buf.pop(), None);
+    }
+
+    #[test]
+    fn full() {
+        let mut buf = CyclicArray::<u32, 3>::new();
+        assert!(buf.push(1));
+        assert!(buf.push(2));
+        assert!(buf.push(3));
+        assert!(buf.full());
+        assert!(!buf.push(4));
+        assert_eq!(buf.pop(), Some(1));
+        assert_eq!(buf.pop(), Some(2));
+        assert_eq!(buf.pop(), Some(3));
+        assert_eq!(buf.pop(), None);
+    }
+
+    #[test]
+    fn force_push() {
+        let mut buf = CyclicArray::<u32, 3>::new();
+        assert!(buf.push(1));
+        assert!(buf.push(2));
+        assert!(buf.push(3));
+        buf.force_push(4);
+        assert!(buf.full());
+        assert_eq!(buf.pop(), Some(2));
+        assert_eq!(buf.pop(), Some(3));
+        assert_eq!(buf.pop(), Some(4));
+    }
+
+    #[test]
+    fn iter_wraps_around() {
+        let mut buf = CyclicArray::<u32, 3>::new();
+        (1..=5).for_each(|value| buf.force_push(value));
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [3, 4, 5]);
+        buf.pop();
+        assert!(buf.push(6));
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [4, 5, 6]);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut buf = CyclicArray::<u32, 0>::new();
+        assert!(buf.empty());
+        assert!(buf.full());
+        assert!(!buf.push(1));
+        buf.force_push(1);
+        assert_eq!(buf.pop(), None);
+        assert!(buf.iter().next().is_none());
+    }
+
+    #[test]
+    fn without_default_or_clone() {
+        struct Token(u32);
+        let mut buf = CyclicArray::<Token, 2>::new();
+        assert!(buf.push(Token(1)));
+        assert_eq!(buf.pop().map(|token| token.0), Some(1));
+    }
+
+    #[test]
+    fn drops_only_live_elements() {
+        let drops = Cell::new(0);
+        {
+            let mut buf = CyclicArray::<DropCounter, 4>::new();
+            (0..3).for_each(|_| {
+                buf.push(DropCounter(&drops));
+            });
+            drop(buf.pop());
+            assert_eq!(drops.get(), 1);
+        }
+        assert_eq!(drops.get(), 3);
+
+        let drops = Cell::new(0);
+        {
+            let mut buf = CyclicArray::<DropCounter, 2>::new();
+            (0..5).for_each(|_| buf.force_push(DropCounter(&drops)));
+            assert_eq!(drops.get(), 3);
+        }
+        assert_eq!(drops.get(), 5);
+    }
+}
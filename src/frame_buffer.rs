@@ -1,9 +1,57 @@
+pub mod draw;
+mod encoder;
+pub mod layer;
+pub mod screenshot;
+pub mod width;
+
+use crate::terminal::{self, Capabilities};
 use crate::types::{Dimensions, Matrix2, Position};
+use layer::{Layer, LayerId};
+
+/// Maximum number of combining marks a cell holds. Further marks are dropped.
+pub const MAX_MARKS: usize = 2;
+
+/// Combining marks drawn over the character of a cell, like the accent of a decomposed `é`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Marks([Option<char>; MAX_MARKS]);
+
+impl Marks {
+    pub const NONE: Marks = Marks([None; MAX_MARKS]);
+
+    /// Adds a mark, or returns `false` if the cell holds `MAX_MARKS` marks already.
+    pub fn push(&mut self, mark: char) -> bool {
+        match self.0.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some(mark);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0[0].is_none()
+    }
 
-#[derive(Clone, PartialEq, Copy)]
+    pub fn iter(&self) -> impl Iterator<Item = char> + '_ {
+        self.0.iter().map_while(|mark| *mark)
+    }
+
+    /// Gets the number of bytes the marks take in UTF-8.
+    pub fn len_utf8(&self) -> usize {
+        self.iter().map(char::len_utf8).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Copy)]
 pub struct Pixel {
     pub character: char,
     pub color: Color,
+    /// Number of terminal columns the character takes. Zero marks the right half of the wide
+    /// character in the cell to the left.
+    pub width: u8,
+    /// Combining marks written after the character.
+    pub marks: Marks,
 }
 
 impl std::default::Default for Pixel {
@@ -11,11 +59,113 @@ impl std::default::Default for Pixel {
         Pixel {
             character: ' ',
             color: Color::default(),
+            width: 1,
+            marks: Marks::NONE,
         }
     }
 }
 
-#[derive(Default, Clone, PartialEq, Copy)]
+/// Writes the character followed by its combining marks.
+impl std::fmt::Display for Pixel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use std::fmt::Write;
+        f.write_char(self.character)?;
+        self.marks.iter().try_for_each(|mark| f.write_char(mark))
+    }
+}
+
+impl Pixel {
+    /// Lets the layers below show through. Cells left transparent by every layer are blank.
+    pub const TRANSPARENT: Pixel = Pixel {
+        character: '\0',
+        color: Color::Default,
+        width: 1,
+        marks: Marks::NONE,
+    };
+
+    /// Constructs a pixel taking as many columns as the character.
+    ///
+    /// A combining mark on its own is put on a space, as Unicode suggests for marks without a
+    /// base character. Control characters cannot be shown and are replaced by U+FFFD.
+    pub fn new(character: char, color: Color) -> Self {
+        let mut pixel = Pixel {
+            character,
+            color,
+            width: width::char_width(character) as u8,
+            marks: Marks::NONE,
+        };
+        if width::is_mark(character) {
+            pixel.character = ' ';
+            pixel.marks.push(character);
+        }
+        if pixel.width == 0 {
+            pixel.width = 1;
+            if pixel.marks.is_empty() {
+                pixel.character = char::REPLACEMENT_CHARACTER;
+            }
+        }
+        pixel
+    }
+
+    /// Constructs the right half of a wide character.
+    pub fn continuation(color: Color) -> Self {
+        Pixel {
+            character: ' ',
+            color,
+            width: 0,
+            marks: Marks::NONE,
+        }
+    }
+
+    /// Gets the number of bytes the character and its marks take in UTF-8.
+    pub fn len_utf8(&self) -> usize {
+        self.character.len_utf8() + self.marks.len_utf8()
+    }
+
+    pub fn is_continuation(&self) -> bool {
+        self.width == 0
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        *self == Self::TRANSPARENT
+    }
+}
+
+impl Matrix2<Pixel> {
+    /// Sets a pixel while keeping wide characters intact.
+    ///
+    /// A wide pixel also covers the cell to its right with a continuation, and the remaining half
+    /// of any wide character that is partly overwritten is blanked. Wide pixels that do not fit
+    /// into the row are replaced by a space.
+    pub fn set_pixel(&mut self, position: &Position, pixel: Pixel) {
+        debug_assert!(!pixel.is_continuation());
+        let (x, y) = (position.x, position.y);
+        let pixel = match pixel.width > 1 && x + 1 >= self.dimensions().x {
+            true => Pixel::new(' ', pixel.color),
+            false => pixel,
+        };
+        self.split_wide(x, y);
+        self.set(x, y, pixel);
+        if pixel.width > 1 {
+            self.split_wide(x + 1, y);
+            self.set(x + 1, y, Pixel::continuation(pixel.color));
+        }
+    }
+
+    /// Blanks the other half if the cell holds half of a wide character.
+    fn split_wide(&mut self, x: usize, y: usize) {
+        let pixel = *self.get(x, y);
+        let other_x = match pixel.width {
+            0 if x > 0 => x - 1,
+            2 if x + 1 < self.dimensions().x => x + 1,
+            _ => return,
+        };
+        let other = *self.get(other_x, y);
+        self.set(other_x, y, Pixel::new(' ', other.color));
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Copy)]
 #[repr(u8)]
 pub enum Color {
     #[default]
@@ -28,25 +178,55 @@ pub enum Color {
     Yellow,
 }
 
+/// Statistics of the most recent buffer swap.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SwapStats {
+    /// Number of bytes written to the terminal.
+    pub bytes_written: usize,
+    /// Number of cells that differed from the previous frame.
+    pub dirty_cells: usize,
+}
+
 pub struct FrameBuffer {
     dimensions: Dimensions,
     buffer1: Matrix2<Pixel>,
     buffer2: Matrix2<Pixel>,
     buffer1_is_front: bool,
     command_cache: Vec<u8>,
+    capabilities: Capabilities,
+    /// Color selected on the terminal by the previous swap, if any.
+    terminal_color: Option<Color>,
+    swap_stats: SwapStats,
+    layers: Vec<Layer>,
+    /// Indices of the layers from the bottom to the top.
+    layer_order: Vec<usize>,
 }
 
 impl FrameBuffer {
+    /// Constructs a frame buffer that only uses control functions every terminal supports.
     pub fn new(dimensions: &Dimensions) -> Self {
+        Self::with_capabilities(dimensions, Capabilities::default())
+    }
+
+    pub fn with_capabilities(dimensions: &Dimensions, capabilities: Capabilities) -> Self {
         Self {
-            dimensions: dimensions.clone(),
+            dimensions: *dimensions,
             buffer1: Matrix2::<Pixel>::new(dimensions),
             buffer2: Matrix2::<Pixel>::new(dimensions),
             buffer1_is_front: true,
-            command_cache: vec![0; Self::command_cache_size(dimensions)],
+            command_cache: Vec::with_capacity(Self::command_cache_size(dimensions)),
+            capabilities,
+            terminal_color: None,
+            swap_stats: SwapStats::default(),
+            layers: Vec::new(),
+            layer_order: Vec::new(),
         }
     }
 
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
     fn command_cache_size(dimensions: &Dimensions) -> usize {
         dimensions.x * dimensions.y * (4 + 5 + 10)
     }
@@ -57,37 +237,54 @@ impl FrameBuffer {
             false => (&self.buffer2, &self.buffer1),
         };
 
-        let mut position = Position { x: 0, y: 0 };
-        let mut last_position = position.clone();
-        let mut last_color = Color::default();
-        let mut i: usize = 0;
-        for (pixel1, pixel2) in front_buffer.iter().zip(back_buffer.iter()) {
-            let mut force_draw_char = false;
-            if *pixel1 != *pixel2
-                && (position.y != last_position.y || position.x != last_position.x + 1)
-            {
-                i += position.encode_ascii(&mut self.command_cache[i..]);
-            }
-            if pixel1.color != pixel2.color {
-                if pixel1.color != last_color {
-                    i += pixel1.color.encode_ascii(&mut self.command_cache[i..]);
-                    last_color = pixel1.color;
-                }
-                force_draw_char = true;
-            }
-            if force_draw_char || pixel1.character != pixel2.character {
-                i += pixel1.encode_ascii(&mut self.command_cache[i..]);
-                last_position = position.clone();
-            }
-            position.x += 1;
-            if position.x == self.dimensions.x {
-                position.x = 0;
-                position.y += 1;
+        self.command_cache.clear();
+        let synchronized = self.capabilities.synchronized_output;
+        if synchronized {
+            self.command_cache
+                .extend_from_slice(terminal::BEGIN_SYNCHRONIZED_UPDATE);
+        }
+        let mut encoder = encoder::Encoder::new(
+            &mut self.command_cache,
+            self.capabilities,
+            self.dimensions.x,
+            self.terminal_color,
+        );
+        let mut dirty_cells: usize = 0;
+        for y in 0..self.dimensions.y {
+            let (front_row, back_row) = (front_buffer.row(y), back_buffer.row(y));
+            dirty_cells += front_row
+                .iter()
+                .zip(back_row)
+                .filter(|(pixel1, pixel2)| !encoder::looks_same(pixel1, pixel2))
+                .count();
+            encoder.encode_row(y, front_row, back_row);
+        }
+        self.terminal_color = encoder.color();
+        if synchronized {
+            match self.command_cache.len() == terminal::BEGIN_SYNCHRONIZED_UPDATE.len() {
+                true => self.command_cache.clear(),
+                false => self
+                    .command_cache
+                    .extend_from_slice(terminal::END_SYNCHRONIZED_UPDATE),
             }
         }
-        &self.command_cache[0..i]
+        self.swap_stats = SwapStats {
+            bytes_written: self.command_cache.len(),
+            dirty_cells,
+        };
+        &self.command_cache
     }
 
+    /// Gets the frame shown on the terminal since the most recent swap.
+    pub fn front_buffer(&self) -> &Matrix2<Pixel> {
+        match self.buffer1_is_front {
+            true => &self.buffer1,
+            false => &self.buffer2,
+        }
+    }
+
+    /// Gets the buffer the next frame is drawn into. It lies beneath all layers and is cleared
+    /// after every swap.
     pub fn back_buffer(&mut self) -> &mut Matrix2<Pixel> {
         match self.buffer1_is_front {
             true => &mut self.buffer2,
@@ -95,14 +292,227 @@ impl FrameBuffer {
         }
     }
 
-    pub fn swap_buffers(&mut self) {
+    /// Gets a canvas for drawing text and widgets into the back buffer.
+    pub fn canvas(&mut self) -> draw::Canvas<'_> {
+        draw::Canvas::new(self.back_buffer())
+    }
+
+    /// Adds a visible, transparent layer.
+    ///
+    /// # Panics
+    /// Panics if a layer with the same name exists.
+    pub fn add_layer(&mut self, name: &str, z_index: i32) -> LayerId {
+        assert!(
+            self.layer_id(name).is_none(),
+            "duplicate layer name: {}",
+            name
+        );
+        let index = self.layers.len();
+        self.layers
+            .push(Layer::new(name, z_index, &self.dimensions));
+        // Layers with the same z-index stay in the order they were added.
+        let position = self
+            .layer_order
+            .partition_point(|&other| self.layers[other].z_index() <= z_index);
+        self.layer_order.insert(position, index);
+        LayerId(index)
+    }
+
+    /// Finds a layer by its name.
+    pub fn layer_id(&self, name: &str) -> Option<LayerId> {
+        self.layers
+            .iter()
+            .position(|layer| layer.name() == name)
+            .map(LayerId)
+    }
+
+    pub fn layer(&self, id: LayerId) -> &Layer {
+        &self.layers[id.0]
+    }
+
+    pub fn layer_mut(&mut self, id: LayerId) -> &mut Layer {
+        &mut self.layers[id.0]
+    }
+
+    /// Draws the visible layers over the back buffer.
+    fn compose(&mut self) {
+        let back_buffer = match self.buffer1_is_front {
+            true => &mut self.buffer2,
+            false => &mut self.buffer1,
+        };
+        for &index in &self.layer_order {
+            let layer = &self.layers[index];
+            if layer.is_visible() {
+                layer.compose_onto(back_buffer);
+            }
+        }
+        layer::fill_transparent(back_buffer);
+    }
+
+    /// Gets the statistics of the most recent call to `swap_buffers`.
+    pub fn swap_stats(&self) -> &SwapStats {
+        &self.swap_stats
+    }
+
+    /// Gets the bytes written by the most recent swap.
+    pub fn last_output(&self) -> &[u8] {
+        &self.command_cache
+    }
+
+    /// Shows the back buffer with the layers composed over it on the terminal.
+    ///
+    /// The changes are written in one piece, as a synchronized update if the terminal supports it.
+    pub fn swap_buffers(&mut self) -> std::io::Result<()> {
         use std::io::Write;
+        // Anything still queued by `print!` has to reach the terminal first.
+        std::io::stdout().flush()?;
+        self.swap_buffers_to(&mut terminal::Output::new())
+    }
 
+    /// Like `swap_buffers`, but writes the changes to any writer instead of the terminal.
+    pub fn swap_buffers_to(&mut self, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        self.compose();
         self.buffer1_is_front = !self.buffer1_is_front;
         let command_cache = self.update_command_cache();
-        let mut stdout = std::io::stdout().lock();
-        stdout.write_all(command_cache).unwrap();
-        stdout.flush().unwrap();
+        writer.write_all(command_cache)?;
+        writer.flush()?;
         self.back_buffer().clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(pixels: &Matrix2<Pixel>) -> Vec<(char, u8)> {
+        pixels
+            .row(0)
+            .iter()
+            .map(|pixel| (pixel.character, pixel.width))
+            .collect()
+    }
+
+    #[test]
+    fn pixel_width() {
+        assert_eq!(Pixel::new('a', Color::Red).width, 1);
+        assert_eq!(Pixel::new('中', Color::Red).width, 2);
+        let combining = Pixel::new('\u{301}', Color::Red);
+        assert_eq!((combining.character, combining.width), (' ', 1));
+        assert_eq!(combining.to_string(), " \u{301}");
+        let control = Pixel::new('\x07', Color::Red);
+        assert_eq!(
+            (control.character, control.width),
+            (char::REPLACEMENT_CHARACTER, 1)
+        );
+        assert!(control.marks.is_empty());
+        assert!(Pixel::continuation(Color::Red).is_continuation());
+        assert!(!Pixel::default().is_continuation());
+        assert!(!Pixel::new('\0', Color::Default).is_transparent());
+    }
+
+    #[test]
+    fn marks() {
+        let mut pixel = Pixel::new('e', Color::Default);
+        assert!(pixel.marks.push('\u{323}'));
+        assert!(pixel.marks.push('\u{302}'));
+        assert!(!pixel.marks.push('\u{301}'));
+        assert_eq!(pixel.to_string(), "e\u{323}\u{302}");
+        assert_eq!(pixel.len_utf8(), 5);
+        let mut buffer = [0; 16];
+        assert_eq!(pixel.encode_ascii(&mut buffer), 5);
+        assert_eq!(&buffer[..5], "e\u{323}\u{302}".as_bytes());
+    }
+
+    #[test]
+    fn set_pixel_keeps_wide_characters_intact() {
+        let mut pixels = Matrix2::new(&Dimensions::new(4, 1));
+        pixels.set_pixel(&Position::new(0, 0), Pixel::new('中', Color::Red));
+        pixels.set_pixel(&Position::new(2, 0), Pixel::new('文', Color::Red));
+        assert_eq!(row(&pixels), [('中', 2), (' ', 0), ('文', 2), (' ', 0)]);
+
+        // Overwriting either half of a wide character blanks the other half.
+        pixels.set_pixel(&Position::new(1, 0), Pixel::new('a', Color::Red));
+        pixels.set_pixel(&Position::new(2, 0), Pixel::new('b', Color::Red));
+        assert_eq!(row(&pixels), [(' ', 1), ('a', 1), ('b', 1), (' ', 1)]);
+
+        pixels.set_pixel(&Position::new(0, 0), Pixel::new('中', Color::Red));
+        pixels.set_pixel(&Position::new(1, 0), Pixel::new('文', Color::Red));
+        assert_eq!(row(&pixels), [(' ', 1), ('文', 2), (' ', 0), (' ', 1)]);
+
+        // Wide characters do not wrap into the next row.
+        pixels.set_pixel(&Position::new(3, 0), Pixel::new('字', Color::Red));
+        assert_eq!(row(&pixels), [(' ', 1), ('文', 2), (' ', 0), (' ', 1)]);
+    }
+
+    #[test]
+    fn swap_stats() {
+        let mut frame_buffer = FrameBuffer::new(&Dimensions::new(4, 2));
+        let mut output = Vec::new();
+        frame_buffer
+            .back_buffer()
+            .set_pixel(&Position::new(1, 1), Pixel::new('中', Color::Default));
+        frame_buffer.swap_buffers_to(&mut output).unwrap();
+        assert_eq!(output, "\x1b[2;2H\x1b[0m中".as_bytes());
+        assert_eq!(frame_buffer.last_output(), output);
+        assert_eq!(
+            frame_buffer.swap_stats(),
+            &SwapStats {
+                bytes_written: output.len(),
+                dirty_cells: 2
+            }
+        );
+    }
+
+    #[test]
+    fn layers() {
+        let mut frame_buffer = FrameBuffer::new(&Dimensions::new(4, 1));
+        let top = frame_buffer.add_layer("top", 1);
+        let bottom = frame_buffer.add_layer("bottom", 0);
+        let middle = frame_buffer.add_layer("middle", 0);
+        assert_eq!(frame_buffer.layer_id("middle"), Some(middle));
+        assert_eq!(frame_buffer.layer_id("missing"), None);
+        assert_eq!(frame_buffer.layer(top).z_index(), 1);
+        frame_buffer
+            .layer_mut(bottom)
+            .canvas()
+            .print(Position::new(0, 0), "abc", Color::Default);
+        frame_buffer
+            .layer_mut(middle)
+            .canvas()
+            .print(Position::new(1, 0), "de", Color::Default);
+        frame_buffer
+            .layer_mut(top)
+            .canvas()
+            .print(Position::new(2, 0), "f", Color::Default);
+        frame_buffer
+            .back_buffer()
+            .set_pixel(&Position::new(3, 0), Pixel::new('g', Color::Default));
+        let text = |frame_buffer: &FrameBuffer| -> String {
+            frame_buffer
+                .front_buffer()
+                .iter()
+                .map(|pixel| pixel.character)
+                .collect()
+        };
+        let mut output = Vec::new();
+        frame_buffer.swap_buffers_to(&mut output).unwrap();
+        assert_eq!(text(&frame_buffer), "adfg");
+
+        // Layers keep their content, unlike the back buffer.
+        frame_buffer.layer_mut(top).set_visible(false);
+        frame_buffer.swap_buffers_to(&mut output).unwrap();
+        assert_eq!(text(&frame_buffer), "ade ");
+        frame_buffer.layer_mut(middle).clear();
+        frame_buffer.swap_buffers_to(&mut output).unwrap();
+        assert_eq!(text(&frame_buffer), "abc ");
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate layer name")]
+    fn duplicate_layer_name() {
+        let mut frame_buffer = FrameBuffer::new(&Dimensions::new(4, 1));
+        frame_buffer.add_layer("walls", 0);
+        frame_buffer.add_layer("walls", 1);
     }
 }
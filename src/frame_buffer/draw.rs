@@ -0,0 +1,477 @@
+//! Drawing text and simple widgets into a [`Matrix2<Pixel>`], usually the back buffer.
+//!
+//! All positions are in cells and everything is clipped to the canvas. Text is laid out by display
+//! width, so wide characters cover two cells and are dropped rather than split when only half of
+//! them fits. Combining marks take no column and are attached to the character before them.
+
+use super::width::{char_width, is_mark, str_width};
+use super::{Color, Pixel};
+use crate::types::{Dimensions, Matrix2, Position, Rect};
+
+/// Horizontal placement of text within an area.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl Alignment {
+    /// Gets the offset of text of the given width within the available width.
+    fn offset(self, width: usize, available: usize) -> usize {
+        let space = available.saturating_sub(width);
+        match self {
+            Alignment::Left => 0,
+            Alignment::Center => space / 2,
+            Alignment::Right => space,
+        }
+    }
+}
+
+/// Characters of the edges and corners of a box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BorderStyle {
+    pub horizontal: char,
+    pub vertical: char,
+    pub top_left: char,
+    pub top_right: char,
+    pub bottom_left: char,
+    pub bottom_right: char,
+}
+
+impl BorderStyle {
+    pub const LIGHT: Self = Self::new('─', '│', ['┌', '┐', '└', '┘']);
+    pub const ROUNDED: Self = Self::new('─', '│', ['╭', '╮', '╰', '╯']);
+    pub const HEAVY: Self = Self::new('━', '┃', ['┏', '┓', '┗', '┛']);
+    pub const DOUBLE: Self = Self::new('═', '║', ['╔', '╗', '╚', '╝']);
+
+    /// Constructs a style from the edges and the corners in reading order.
+    pub const fn new(horizontal: char, vertical: char, corners: [char; 4]) -> Self {
+        Self {
+            horizontal,
+            vertical,
+            top_left: corners[0],
+            top_right: corners[1],
+            bottom_left: corners[2],
+            bottom_right: corners[3],
+        }
+    }
+}
+
+/// Partially filled cells of a progress bar in eighths.
+const PROGRESS_EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+const PROGRESS_FULL: char = '█';
+const PROGRESS_EMPTY: char = '░';
+
+/// Number of blank cells between the columns of a table.
+const COLUMN_GAP: usize = 2;
+
+/// Gets the longest prefix of a line that fits into the given number of columns and its width.
+fn truncate(text: &str, columns: usize) -> (&str, usize) {
+    let mut width = 0;
+    for (index, character) in text.char_indices() {
+        let character_width = char_width(character);
+        if width + character_width > columns {
+            return (&text[..index], width);
+        }
+        width += character_width;
+    }
+    (text, width)
+}
+
+/// Clipped drawing surface over a matrix of pixels.
+pub struct Canvas<'a> {
+    pixels: &'a mut Matrix2<Pixel>,
+    clip: Rect,
+}
+
+impl<'a> Canvas<'a> {
+    /// Constructs a canvas covering the whole matrix.
+    pub fn new(pixels: &'a mut Matrix2<Pixel>) -> Self {
+        let clip = pixels.bounds();
+        Self { pixels, clip }
+    }
+
+    /// Gets the area drawing is restricted to.
+    pub fn clip(&self) -> &Rect {
+        &self.clip
+    }
+
+    /// Gets a canvas that only draws within the part of `rect` inside this canvas.
+    pub fn clipped(&mut self, rect: &Rect) -> Canvas<'_> {
+        Canvas {
+            clip: self.clip.intersect(rect).unwrap_or_default(),
+            pixels: self.pixels,
+        }
+    }
+
+    /// Draws a single character and gets the number of columns it takes.
+    ///
+    /// Characters that do not fit completely into the clip area are not drawn, but still take
+    /// their columns. Combining marks are added to the cell to the left of the position, like a
+    /// terminal does, if that cell is within the clip area.
+    pub fn put(&mut self, position: Position, character: char, color: Color) -> usize {
+        if is_mark(character) {
+            self.attach_mark(position, character);
+            return 0;
+        }
+        let width = char_width(character);
+        let last = Position::new(position.x + width.saturating_sub(1), position.y);
+        if width > 0 && self.clip.contains(&position) && self.clip.contains(&last) {
+            self.pixels
+                .set_pixel(&position, Pixel::new(character, color));
+        }
+        width
+    }
+
+    fn attach_mark(&mut self, position: Position, mark: char) {
+        let Some(x) = position.x.checked_sub(1) else {
+            return;
+        };
+        let mut base = Position::new(x, position.y);
+        if self.clip.contains(&base) && self.pixels[base].is_continuation() && x > 0 {
+            base.x -= 1;
+        }
+        if self.clip.contains(&base) && !self.pixels[base].is_transparent() {
+            self.pixels[base].marks.push(mark);
+        }
+    }
+
+    /// Draws a line of text starting at a position and gets the number of columns it takes.
+    pub fn print(&mut self, position: Position, text: &str, color: Color) -> usize {
+        let mut x = position.x;
+        for character in text.chars() {
+            x += self.put(Position::new(x, position.y), character, color);
+        }
+        x - position.x
+    }
+
+    /// Draws text line by line into the rows of an area, aligning and truncating every line to
+    /// the width of the area.
+    pub fn draw_text(&mut self, area: &Rect, text: &str, color: Color, alignment: Alignment) {
+        let mut canvas = self.clipped(area);
+        for (row, line) in text.lines().take(area.dimensions.y).enumerate() {
+            let (line, width) = truncate(line, area.dimensions.x);
+            let position = Position::new(
+                area.origin.x + alignment.offset(width, area.dimensions.x),
+                area.origin.y + row,
+            );
+            canvas.print(position, line, color);
+        }
+    }
+
+    /// Sets all cells of an area to the same narrow pixel.
+    pub fn fill(&mut self, rect: &Rect, pixel: Pixel) {
+        debug_assert_eq!(pixel.width, 1);
+        if let Some(rect) = self.clip.intersect(rect) {
+            for y in rect.origin.y..rect.bottom() {
+                self.pixels.split_wide(rect.origin.x, y);
+                self.pixels.split_wide(rect.right() - 1, y);
+            }
+            self.pixels.fill_rect(&rect, pixel);
+        }
+    }
+
+    /// Draws the outline of a box. Boxes smaller than 2×2 cells are not drawn.
+    pub fn draw_box(&mut self, rect: &Rect, style: &BorderStyle, color: Color) {
+        if rect.dimensions.x < 2 || rect.dimensions.y < 2 {
+            return;
+        }
+        let (left, top) = (rect.origin.x, rect.origin.y);
+        let (right, bottom) = (rect.right() - 1, rect.bottom() - 1);
+        for x in left + 1..right {
+            self.put(Position::new(x, top), style.horizontal, color);
+            self.put(Position::new(x, bottom), style.horizontal, color);
+        }
+        for y in top + 1..bottom {
+            self.put(Position::new(left, y), style.vertical, color);
+            self.put(Position::new(right, y), style.vertical, color);
+        }
+        self.put(Position::new(left, top), style.top_left, color);
+        self.put(Position::new(right, top), style.top_right, color);
+        self.put(Position::new(left, bottom), style.bottom_left, color);
+        self.put(Position::new(right, bottom), style.bottom_right, color);
+    }
+
+    /// Draws a modal dialog centered on the canvas, with the title in its top border and the
+    /// lines of the body centered inside. Gets the area of the dialog including its border.
+    pub fn draw_dialog(&mut self, title: &str, body: &str, color: Color) -> Rect {
+        let title = match title.is_empty() {
+            true => String::new(),
+            false => format!(" {} ", title),
+        };
+        let content_width = body
+            .lines()
+            .map(str_width)
+            .chain(std::iter::once(str_width(&title)))
+            .max()
+            .unwrap_or(0);
+        let dimensions = Dimensions::new(
+            std::cmp::min(content_width + 4, self.clip.dimensions.x),
+            std::cmp::min(body.lines().count() + 4, self.clip.dimensions.y),
+        );
+        let origin = Position::new(
+            self.clip.origin.x + (self.clip.dimensions.x - dimensions.x) / 2,
+            self.clip.origin.y + (self.clip.dimensions.y - dimensions.y) / 2,
+        );
+        let rect = Rect::new(origin, dimensions);
+        self.fill(&rect, Pixel::new(' ', color));
+        self.draw_box(&rect, &BorderStyle::LIGHT, color);
+        let title_area = Rect::new(
+            origin + Position::new(1, 0),
+            Dimensions::new(dimensions.x.saturating_sub(2), 1),
+        );
+        self.draw_text(&title_area, &title, color, Alignment::Center);
+        let body_area = Rect::new(
+            origin + Position::new(2, 2),
+            Dimensions::new(
+                dimensions.x.saturating_sub(4),
+                dimensions.y.saturating_sub(4),
+            ),
+        );
+        self.draw_text(&body_area, body, color, Alignment::Center);
+        rect
+    }
+
+    /// Draws a progress bar into the top row of an area, filled in eighths of a cell.
+    pub fn draw_progress_bar(&mut self, area: &Rect, fraction: f64, color: Color) {
+        let width = area.dimensions.x;
+        if area.dimensions.y == 0 {
+            return;
+        }
+        let eighths = (fraction.clamp(0.0, 1.0) * (8 * width) as f64).round() as usize;
+        let (full, partial) = (eighths / 8, eighths % 8);
+        for i in 0..width {
+            let character = match i.cmp(&full) {
+                std::cmp::Ordering::Less => PROGRESS_FULL,
+                std::cmp::Ordering::Equal if partial > 0 => PROGRESS_EIGHTHS[partial - 1],
+                _ => PROGRESS_EMPTY,
+            };
+            self.put(area.origin + Position::new(i, 0), character, color);
+        }
+    }
+
+    /// Draws rows of cells as a table with every column as wide as its widest cell.
+    ///
+    /// Columns without an alignment are aligned left. Gets the area covered by the table.
+    pub fn draw_table(
+        &mut self,
+        origin: Position,
+        rows: &[&[&str]],
+        alignments: &[Alignment],
+        color: Color,
+    ) -> Rect {
+        let mut widths: Vec<usize> = Vec::new();
+        for row in rows {
+            for (column, cell) in row.iter().enumerate() {
+                match widths.get_mut(column) {
+                    Some(width) => *width = std::cmp::max(*width, str_width(cell)),
+                    None => widths.push(str_width(cell)),
+                }
+            }
+        }
+        for (y, row) in rows.iter().enumerate() {
+            let mut x = origin.x;
+            for (column, cell) in row.iter().enumerate() {
+                let area = Rect::new(
+                    Position::new(x, origin.y + y),
+                    Dimensions::new(widths[column], 1),
+                );
+                let alignment = alignments.get(column).copied().unwrap_or_default();
+                self.draw_text(&area, cell, color, alignment);
+                x += widths[column] + COLUMN_GAP;
+            }
+        }
+        let width = widths.iter().sum::<usize>() + COLUMN_GAP * widths.len().saturating_sub(1);
+        Rect::new(origin, Dimensions::new(width, rows.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(pixels: &Matrix2<Pixel>, y: usize) -> String {
+        pixels
+            .row(y)
+            .iter()
+            .map(|pixel| pixel.to_string())
+            .collect()
+    }
+
+    fn rows(pixels: &Matrix2<Pixel>) -> Vec<String> {
+        (0..pixels.dimensions().y).map(|y| row(pixels, y)).collect()
+    }
+
+    #[test]
+    fn print_clips_at_the_edge() {
+        let mut pixels = Matrix2::new(&Dimensions::new(6, 1));
+        let width = Canvas::new(&mut pixels).print(Position::new(2, 0), "hello", Color::Red);
+        assert_eq!(width, 5);
+        assert_eq!(row(&pixels, 0), "  hell");
+        assert!(pixels.get(2, 0).color == Color::Red);
+    }
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        let mut pixels = Matrix2::new(&Dimensions::new(6, 1));
+        let width = Canvas::new(&mut pixels).print(Position::new(0, 0), "中a文", Color::Default);
+        assert_eq!(width, 5);
+        assert_eq!(row(&pixels, 0), "中 a文  ");
+
+        // Half a wide character is never drawn.
+        let mut pixels = Matrix2::new(&Dimensions::new(3, 1));
+        Canvas::new(&mut pixels).print(Position::new(0, 0), "a中文", Color::Default);
+        assert_eq!(row(&pixels, 0), "a中 ");
+    }
+
+    #[test]
+    fn combining_marks_take_no_column() {
+        let mut pixels = Matrix2::new(&Dimensions::new(4, 1));
+        let width = Canvas::new(&mut pixels).print(
+            Position::new(0, 0),
+            "e\u{301}te\u{301}",
+            Color::Default,
+        );
+        assert_eq!(width, 3);
+        assert_eq!(row(&pixels, 0), "e\u{301}te\u{301} ");
+        assert_eq!(
+            pixels.get(0, 0).marks.iter().collect::<Vec<_>>(),
+            ['\u{301}']
+        );
+
+        // Marks follow wide characters and are not attached outside the clip area.
+        let mut pixels = Matrix2::new(&Dimensions::new(4, 1));
+        let mut canvas = Canvas::new(&mut pixels);
+        canvas.print(Position::new(0, 0), "中\u{301}a", Color::Default);
+        canvas
+            .clipped(&Rect::new(Position::new(3, 0), Dimensions::new(1, 1)))
+            .print(Position::new(3, 0), "\u{301}b", Color::Default);
+        assert_eq!(row(&pixels, 0), "中\u{301} ab");
+        Canvas::new(&mut pixels).print(Position::new(3, 0), "cd\u{301}", Color::Default);
+        assert_eq!(row(&pixels, 0), "中\u{301} ac");
+    }
+
+    #[test]
+    fn draw_text_aligns_and_truncates() {
+        let mut pixels = Matrix2::new(&Dimensions::new(8, 4));
+        let mut canvas = Canvas::new(&mut pixels);
+        let area = Rect::new(Position::new(1, 0), Dimensions::new(6, 1));
+        canvas.draw_text(&area, "ab", Color::Default, Alignment::Left);
+        let area = Rect::new(Position::new(1, 1), Dimensions::new(6, 1));
+        canvas.draw_text(&area, "ab", Color::Default, Alignment::Center);
+        let area = Rect::new(Position::new(1, 2), Dimensions::new(6, 1));
+        canvas.draw_text(&area, "中ab", Color::Default, Alignment::Right);
+        let area = Rect::new(Position::new(1, 3), Dimensions::new(6, 1));
+        canvas.draw_text(&area, "truncated", Color::Default, Alignment::Right);
+        assert_eq!(
+            rows(&pixels),
+            [" ab     ", "   ab   ", "   中 ab ", " trunca "]
+        );
+    }
+
+    #[test]
+    fn draw_text_splits_lines() {
+        let mut pixels = Matrix2::new(&Dimensions::new(3, 2));
+        Canvas::new(&mut pixels).draw_text(
+            &Rect::new(Position::new(0, 0), Dimensions::new(3, 2)),
+            "a\nbc\nd",
+            Color::Default,
+            Alignment::Right,
+        );
+        assert_eq!(rows(&pixels), ["  a", " bc"]);
+    }
+
+    #[test]
+    fn clipped_canvas() {
+        let mut pixels = Matrix2::new(&Dimensions::new(5, 2));
+        let mut canvas = Canvas::new(&mut pixels);
+        let mut inner = canvas.clipped(&Rect::new(Position::new(1, 1), Dimensions::new(9, 9)));
+        assert_eq!(
+            inner.clip(),
+            &Rect::new(Position::new(1, 1), Dimensions::new(4, 1))
+        );
+        inner.print(Position::new(0, 0), "hidden", Color::Default);
+        inner.print(Position::new(0, 1), "abcdef", Color::Default);
+        assert_eq!(rows(&pixels), ["     ", " bcde"]);
+    }
+
+    #[test]
+    fn fill_splits_wide_characters() {
+        let mut pixels = Matrix2::new(&Dimensions::new(6, 1));
+        let mut canvas = Canvas::new(&mut pixels);
+        canvas.print(Position::new(0, 0), "中文字", Color::Default);
+        canvas.fill(
+            &Rect::new(Position::new(1, 0), Dimensions::new(4, 1)),
+            Pixel::new('.', Color::Default),
+        );
+        assert_eq!(row(&pixels, 0), " .... ");
+        assert!(pixels.row(0).iter().all(|pixel| pixel.width == 1));
+    }
+
+    #[test]
+    fn draw_box() {
+        let mut pixels = Matrix2::new(&Dimensions::new(5, 4));
+        let mut canvas = Canvas::new(&mut pixels);
+        let rect = Rect::new(Position::new(0, 0), Dimensions::new(4, 3));
+        canvas.draw_box(&rect, &BorderStyle::LIGHT, Color::Default);
+        let rect = Rect::new(Position::new(3, 2), Dimensions::new(4, 3));
+        canvas.draw_box(&rect, &BorderStyle::DOUBLE, Color::Default);
+        assert_eq!(rows(&pixels), ["┌──┐ ", "│  │ ", "└──╔═", "   ║ "]);
+    }
+
+    #[test]
+    fn draw_dialog_is_centered() {
+        let mut pixels = Matrix2::new(&Dimensions::new(13, 7));
+        let rect = Canvas::new(&mut pixels).draw_dialog("Pause", "Score", Color::Default);
+        assert_eq!(rect, Rect::new(Position::new(1, 1), Dimensions::new(11, 5)));
+        assert_eq!(
+            rows(&pixels),
+            [
+                "             ",
+                " ┌─ Pause ─┐ ",
+                " │         │ ",
+                " │  Score  │ ",
+                " │         │ ",
+                " └─────────┘ ",
+                "             ",
+            ]
+        );
+    }
+
+    #[test]
+    fn draw_dialog_is_clipped() {
+        let mut pixels = Matrix2::new(&Dimensions::new(6, 3));
+        let rect = Canvas::new(&mut pixels).draw_dialog("Game over", "Final score", Color::Default);
+        assert_eq!(rect, pixels.bounds());
+        assert_eq!(rows(&pixels), ["┌ Gam┐", "│    │", "└────┘"]);
+    }
+
+    #[test]
+    fn draw_progress_bar() {
+        let mut pixels = Matrix2::new(&Dimensions::new(4, 3));
+        let mut canvas = Canvas::new(&mut pixels);
+        let bar = |y| Rect::new(Position::new(0, y), Dimensions::new(4, 1));
+        canvas.draw_progress_bar(&bar(0), 0.0, Color::Default);
+        canvas.draw_progress_bar(&bar(1), 0.5 + 2.0 / 32.0, Color::Default);
+        canvas.draw_progress_bar(&bar(2), 2.0, Color::Default);
+        assert_eq!(rows(&pixels), ["░░░░", "██▎░", "████"]);
+    }
+
+    #[test]
+    fn draw_table() {
+        let mut pixels = Matrix2::new(&Dimensions::new(12, 3));
+        let rect = Canvas::new(&mut pixels).draw_table(
+            Position::new(1, 0),
+            &[&["Name", "Score"], &["蛇", "7"], &["ab"]],
+            &[Alignment::Left, Alignment::Right],
+            Color::Default,
+        );
+        assert_eq!(rect, Rect::new(Position::new(1, 0), Dimensions::new(11, 3)));
+        assert_eq!(
+            rows(&pixels),
+            [" Name  Score", " 蛇         7", " ab         "]
+        );
+    }
+}
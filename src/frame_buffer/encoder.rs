@@ -0,0 +1,376 @@
+//! Encodes the difference between two frames into as few bytes as possible.
+//!
+//! Every cursor movement is chosen as the cheapest of an absolute position, relative moves
+//! (`CUU`/`CUD`/`CUF`/`CUB`), carriage returns and line feeds, or rewriting the unchanged cells in
+//! between. Runs of identical cells use `ECH` or `REP` where the terminal supports them. Since only
+//! foreground colors are used, blanks are written without changing the color.
+//!
+//! Line feeds are expected to keep the column, i.e. output post-processing must be disabled as
+//! in raw mode.
+
+use super::{Color, Pixel};
+use crate::terminal::{Capabilities, ControlFunction};
+use crate::types::Position;
+use std::ops::Range;
+
+/// Checks whether two cells look the same on the screen.
+///
+/// Blanks look the same regardless of their color.
+pub(super) fn looks_same(pixel1: &Pixel, pixel2: &Pixel) -> bool {
+    pixel1 == pixel2 || (is_blank(pixel1) && is_blank(pixel2))
+}
+
+fn is_blank(pixel: &Pixel) -> bool {
+    pixel.character == ' ' && pixel.width == 1 && pixel.marks.is_empty()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Horizontal {
+    Stay,
+    Forward(usize),
+    Backward(usize),
+    /// Writes the cells in between again.
+    Rewrite(Range<usize>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Vertical {
+    Stay,
+    LineFeeds(usize),
+    Down(usize),
+    Up(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Move {
+    Absolute,
+    Relative {
+        carriage_return: bool,
+        vertical: Vertical,
+        horizontal: Horizontal,
+    },
+}
+
+pub(super) struct Encoder<'a> {
+    output: &'a mut Vec<u8>,
+    capabilities: Capabilities,
+    width: usize,
+    /// Position of the terminal's cursor, if known. After writing into the last column `x` equals
+    /// the width, since the terminal only wraps when the next character arrives.
+    cursor: Option<Position>,
+    /// Color selected on the terminal, if known.
+    color: Option<Color>,
+}
+
+impl<'a> Encoder<'a> {
+    pub(super) fn new(
+        output: &'a mut Vec<u8>,
+        capabilities: Capabilities,
+        width: usize,
+        color: Option<Color>,
+    ) -> Self {
+        Self {
+            output,
+            capabilities,
+            width,
+            cursor: None,
+            color,
+        }
+    }
+
+    /// Gets the color selected on the terminal after encoding, if known.
+    pub(super) fn color(&self) -> Option<Color> {
+        self.color
+    }
+
+    /// Encodes the changes of a row of cells.
+    pub(super) fn encode_row(&mut self, y: usize, front_row: &[Pixel], back_row: &[Pixel]) {
+        let dirty = |x: usize| !looks_same(&front_row[x], &back_row[x]);
+        let mut x = 0;
+        while x < self.width {
+            let pixel = front_row[x];
+            // Continuations are written along with the wide character to their left.
+            if pixel.is_continuation() {
+                x += 1;
+                continue;
+            }
+            let end = std::cmp::min(x + pixel.width as usize, self.width);
+            if !(x..end).any(dirty) {
+                x += 1;
+                continue;
+            }
+            self.move_to(Position { x, y }, front_row);
+            let count = match pixel.width {
+                1 => {
+                    let same = front_row[x..]
+                        .iter()
+                        .take_while(|other| looks_same(other, &pixel) && other.width == 1)
+                        .count();
+                    // Ends the run with its last changed cell.
+                    (x..x + same).rev().find(|&x| dirty(x)).unwrap() + 1 - x
+                }
+                _ => 1,
+            };
+            self.write_run(&pixel, count);
+            x += count * pixel.width as usize;
+        }
+    }
+
+    fn push(&mut self, encode: impl FnOnce(&mut [u8]) -> usize) {
+        let mut buffer = [0u8; 32];
+        let len = encode(&mut buffer);
+        self.output.extend_from_slice(&buffer[..len]);
+    }
+
+    fn set_color(&mut self, color: Color) {
+        if self.color != Some(color) {
+            self.push(|buffer| color.encode_ascii(buffer));
+            self.color = Some(color);
+        }
+    }
+
+    /// Writes the same pixel into `count` cells from the cursor on.
+    fn write_run(&mut self, pixel: &Pixel, count: usize) {
+        let literal = count * pixel.len_utf8();
+        let repeat = ControlFunction::Repeat(count - 1);
+        let erase = ControlFunction::EraseCharacter(count);
+        if is_blank(pixel) && self.capabilities.erase_character && erase.encoded_len() < literal {
+            // Erasing does not move the cursor.
+            self.push(|buffer| erase.encode_ascii(buffer));
+            return;
+        }
+        if let Some(cursor) = self.cursor.as_mut() {
+            cursor.x += count * pixel.width as usize;
+        }
+        if !is_blank(pixel) {
+            self.set_color(pixel.color);
+        }
+        self.push(|buffer| pixel.encode_ascii(buffer));
+        // `REP` would repeat the last mark rather than the whole cell.
+        if self.capabilities.repeat_character
+            && count > 1
+            && pixel.marks.is_empty()
+            && pixel.len_utf8() + repeat.encoded_len() < literal
+        {
+            self.push(|buffer| repeat.encode_ascii(buffer));
+        } else {
+            for _ in 1..count {
+                self.push(|buffer| pixel.encode_ascii(buffer));
+            }
+        }
+    }
+
+    /// Gets the cost of writing cells of a row again, if they can be written in the current color
+    /// and are cheaper than `limit`.
+    fn rewrite_cost(&self, row: &[Pixel], columns: Range<usize>, limit: usize) -> Option<usize> {
+        if columns.len() >= limit {
+            return None;
+        }
+        let cost = row[columns].iter().try_fold(0, |cost, pixel| {
+            let usable = is_blank(pixel) || (pixel.width == 1 && self.color == Some(pixel.color));
+            usable.then_some(cost + pixel.len_utf8())
+        })?;
+        (cost < limit).then_some(cost)
+    }
+
+    /// Gets the cheapest way to move right within the target row.
+    fn forward(&self, row: &[Pixel], from: usize, to: usize) -> (Horizontal, usize) {
+        if from == to {
+            return (Horizontal::Stay, 0);
+        }
+        let cost = ControlFunction::CursorForward(to - from).encoded_len();
+        match self.rewrite_cost(row, from..to, cost) {
+            Some(rewrite_cost) => (Horizontal::Rewrite(from..to), rewrite_cost),
+            None => (Horizontal::Forward(to - from), cost),
+        }
+    }
+
+    /// Gets the cheapest way to move within the target row, including a leading carriage return.
+    fn horizontal(&self, row: &[Pixel], from: usize, to: usize) -> (bool, Horizontal, usize) {
+        let (horizontal, cost) = self.forward(row, 0, to);
+        let mut best = (true, horizontal, 1 + cost);
+        // Relative moves are undefined while a wrap is pending.
+        if from < self.width {
+            let (horizontal, cost) = match to.cmp(&from) {
+                std::cmp::Ordering::Less => {
+                    let function = ControlFunction::CursorBackward(from - to);
+                    (Horizontal::Backward(from - to), function.encoded_len())
+                }
+                _ => self.forward(row, from, to),
+            };
+            if cost < best.2 {
+                best = (false, horizontal, cost);
+            }
+        }
+        best
+    }
+
+    fn vertical(from: usize, to: usize) -> (Vertical, usize) {
+        match to.cmp(&from) {
+            std::cmp::Ordering::Equal => (Vertical::Stay, 0),
+            std::cmp::Ordering::Less => (
+                Vertical::Up(from - to),
+                ControlFunction::CursorUp(from - to).encoded_len(),
+            ),
+            std::cmp::Ordering::Greater => {
+                let count = to - from;
+                let cost = ControlFunction::CursorDown(count).encoded_len();
+                match count < cost {
+                    true => (Vertical::LineFeeds(count), count),
+                    false => (Vertical::Down(count), cost),
+                }
+            }
+        }
+    }
+
+    /// Gets the cheapest way to move the cursor to a position in the given row.
+    fn plan(&self, target: Position, row: &[Pixel]) -> Move {
+        let Some(cursor) = self.cursor else {
+            return Move::Absolute;
+        };
+        let (vertical, vertical_cost) = Self::vertical(cursor.y, target.y);
+        let (carriage_return, horizontal, horizontal_cost) =
+            self.horizontal(row, cursor.x, target.x);
+        match vertical_cost + horizontal_cost < target.encoded_len() {
+            true => Move::Relative {
+                carriage_return,
+                vertical,
+                horizontal,
+            },
+            false => Move::Absolute,
+        }
+    }
+
+    fn move_to(&mut self, target: Position, row: &[Pixel]) {
+        if self.cursor == Some(target) {
+            return;
+        }
+        match self.plan(target, row) {
+            Move::Absolute => self.push(|buffer| target.encode_ascii(buffer)),
+            Move::Relative {
+                carriage_return,
+                vertical,
+                horizontal,
+            } => {
+                if carriage_return {
+                    self.output.push(b'\r');
+                }
+                let function = match vertical {
+                    Vertical::Stay => None,
+                    Vertical::LineFeeds(count) => {
+                        self.output.extend(std::iter::repeat_n(b'\n', count));
+                        None
+                    }
+                    Vertical::Down(count) => Some(ControlFunction::CursorDown(count)),
+                    Vertical::Up(count) => Some(ControlFunction::CursorUp(count)),
+                };
+                if let Some(function) = function {
+                    self.push(|buffer| function.encode_ascii(buffer));
+                }
+                let function = match horizontal {
+                    Horizontal::Stay => None,
+                    Horizontal::Forward(count) => Some(ControlFunction::CursorForward(count)),
+                    Horizontal::Backward(count) => Some(ControlFunction::CursorBackward(count)),
+                    Horizontal::Rewrite(columns) => {
+                        for pixel in &row[columns] {
+                            self.push(|buffer| pixel.encode_ascii(buffer));
+                        }
+                        None
+                    }
+                };
+                if let Some(function) = function {
+                    self.push(|buffer| function.encode_ascii(buffer));
+                }
+            }
+        }
+        self.cursor = Some(target);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(capabilities: Capabilities, front: &[&str], back: &[&str]) -> String {
+        let pixels = |line: &str| -> Vec<Pixel> {
+            line.chars()
+                .map(|character| Pixel::new(character, Color::Default))
+                .collect()
+        };
+        let mut output = Vec::new();
+        let mut encoder = Encoder::new(
+            &mut output,
+            capabilities,
+            front[0].len(),
+            Some(Color::Default),
+        );
+        for (y, (front_row, back_row)) in front.iter().zip(back).enumerate() {
+            encoder.encode_row(y, &pixels(front_row), &pixels(back_row));
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    const NONE: Capabilities = Capabilities {
+        erase_character: false,
+        repeat_character: false,
+        synchronized_output: false,
+    };
+    const ALL: Capabilities = Capabilities {
+        erase_character: true,
+        repeat_character: true,
+        synchronized_output: false,
+    };
+
+    #[test]
+    fn cheapest_moves() {
+        // Short gaps are rewritten, long ones skipped.
+        assert_eq!(
+            encode(NONE, &["a.b.......c"], &["..........."]),
+            "\x1b[1;1Ha.b\x1b[7Cc"
+        );
+        // Line feeds and carriage returns.
+        assert_eq!(
+            encode(
+                NONE,
+                &["....a", "b....", "....."],
+                &[".....", ".....", "....."]
+            ),
+            "\x1b[1;5Ha\r\nb"
+        );
+        assert_eq!(
+            encode(
+                NONE,
+                &["a....", ".....", "....b"],
+                &[".....", ".....", "....."]
+            ),
+            "\x1b[1;1Ha\n\n...b"
+        );
+    }
+
+    #[test]
+    fn runs() {
+        let front = ["xxxxxxxx      "];
+        let back = [".............."];
+        assert_eq!(encode(NONE, &front, &back), "\x1b[1;1Hxxxxxxxx      ");
+        assert_eq!(encode(ALL, &front, &back), "\x1b[1;1Hx\x1b[7b\x1b[6X");
+        // Unchanged cells at the end of a run are not written.
+        assert_eq!(
+            encode(ALL, &["xxxxxxxxxxxx"], &["........xxxx"]),
+            "\x1b[1;1Hx\x1b[7b"
+        );
+    }
+
+    #[test]
+    fn blanks_keep_the_color() {
+        let mut output = Vec::new();
+        let mut encoder = Encoder::new(&mut output, NONE, 3, None);
+        let front = [
+            Pixel::new('a', Color::Red),
+            Pixel::new(' ', Color::Green),
+            Pixel::new('b', Color::Red),
+        ];
+        encoder.encode_row(0, &front, &[Pixel::new('.', Color::Default); 3]);
+        assert_eq!(encoder.color(), Some(Color::Red));
+        assert_eq!(output, b"\x1b[1;1H\x1b[31ma b");
+    }
+}
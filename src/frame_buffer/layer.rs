@@ -0,0 +1,135 @@
+//! Layers composed over the back buffer when the buffers are swapped.
+//!
+//! A layer keeps its content until it is changed or cleared, so static content like the walls is
+//! drawn once, and overlays can be shown, hidden or redrawn without repainting what lies beneath.
+//! Cells holding `Pixel::TRANSPARENT` show the layers below.
+
+use super::{draw, Pixel};
+use crate::types::{Dimensions, Matrix2};
+
+/// Identifies a layer of a frame buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LayerId(pub(super) usize);
+
+pub struct Layer {
+    name: String,
+    z_index: i32,
+    visible: bool,
+    pixels: Matrix2<Pixel>,
+}
+
+impl Layer {
+    /// Constructs a visible layer that is transparent everywhere.
+    pub(super) fn new(name: &str, z_index: i32, dimensions: &Dimensions) -> Self {
+        let mut layer = Self {
+            name: name.to_string(),
+            z_index,
+            visible: true,
+            pixels: Matrix2::new(dimensions),
+        };
+        layer.clear();
+        layer
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the position in the stack of layers. Layers with a higher index cover those with a
+    /// lower one, and layers with the same index are stacked in the order they were added.
+    pub fn z_index(&self) -> i32 {
+        self.z_index
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Shows or hides the layer without changing its content.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn pixels(&self) -> &Matrix2<Pixel> {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut Matrix2<Pixel> {
+        &mut self.pixels
+    }
+
+    /// Gets a canvas for drawing text and widgets into the layer.
+    pub fn canvas(&mut self) -> draw::Canvas<'_> {
+        draw::Canvas::new(&mut self.pixels)
+    }
+
+    /// Makes every cell transparent.
+    pub fn clear(&mut self) {
+        self.pixels
+            .iter_mut()
+            .for_each(|pixel| *pixel = Pixel::TRANSPARENT);
+    }
+
+    /// Draws the opaque cells of the layer over the target.
+    pub(super) fn compose_onto(&self, target: &mut Matrix2<Pixel>) {
+        for (position, pixel) in self.pixels.enumerate() {
+            // Continuations are set along with the wide character to their left.
+            if !pixel.is_transparent() && !pixel.is_continuation() {
+                target.set_pixel(&position, *pixel);
+            }
+        }
+    }
+}
+
+/// Replaces the transparent cells that are left after composing by blanks.
+pub(super) fn fill_transparent(target: &mut Matrix2<Pixel>) {
+    for pixel in target.iter_mut().filter(|pixel| pixel.is_transparent()) {
+        *pixel = Pixel::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_buffer::Color;
+    use crate::types::Position;
+
+    fn text(pixels: &Matrix2<Pixel>) -> String {
+        pixels
+            .iter()
+            .filter(|pixel| !pixel.is_continuation())
+            .map(|pixel| match pixel.is_transparent() {
+                true => '.',
+                false => pixel.character,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn compose() {
+        let dimensions = Dimensions::new(6, 1);
+        let mut layer = Layer::new("overlay", 1, &dimensions);
+        assert_eq!(text(layer.pixels()), "......");
+        layer.canvas().print(Position::new(1, 0), "a中", Color::Red);
+        assert_eq!(text(layer.pixels()), ".a中..");
+
+        let mut target = Matrix2::new(&dimensions);
+        target.set_pixel(&Position::new(0, 0), Pixel::new('文', Color::Green));
+        target.set_pixel(&Position::new(4, 0), Pixel::new('b', Color::Green));
+        layer.compose_onto(&mut target);
+        // The wide character below is partly covered and loses its other half.
+        assert_eq!(text(&target), " a中b ");
+        assert_eq!(target.get(1, 0).color, Color::Red);
+
+        layer.clear();
+        assert_eq!(text(layer.pixels()), "......");
+    }
+
+    #[test]
+    fn transparent_cells_become_blank() {
+        let mut target = Matrix2::new(&Dimensions::new(3, 1));
+        target.set(1, 0, Pixel::TRANSPARENT);
+        fill_transparent(&mut target);
+        assert!(target.iter().all(|pixel| *pixel == Pixel::default()));
+    }
+}
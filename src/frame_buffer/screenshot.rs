@@ -0,0 +1,292 @@
+//! Exports frames as standalone SVG and HTML documents, e.g. for bug reports.
+//!
+//! Both formats take any matrix of pixels, so frames can be exported without a terminal.
+
+use super::{Color, Pixel};
+use crate::types::Matrix2;
+use std::fmt::Write;
+
+/// Width of a cell in the SVG, in pixels.
+const CELL_WIDTH: f64 = 8.4;
+/// Height of a cell in the SVG, in pixels.
+const CELL_HEIGHT: f64 = 17.0;
+const FONT_SIZE: f64 = 14.0;
+/// Distance from the top of a cell to the baseline of its text.
+const BASELINE: f64 = 13.0;
+const FONT_FAMILY: &str = "'DejaVu Sans Mono', Menlo, Consolas, monospace";
+
+/// CSS colors the terminal colors are exported in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    pub background: &'static str,
+    pub default: &'static str,
+    pub white: &'static str,
+    pub black: &'static str,
+    pub red: &'static str,
+    pub green: &'static str,
+    pub blue: &'static str,
+    pub yellow: &'static str,
+}
+
+impl Palette {
+    /// The VGA colors on a black background.
+    pub const DARK: Palette = Palette {
+        background: "#000000",
+        default: "#aaaaaa",
+        white: "#ffffff",
+        black: "#555555",
+        red: "#aa0000",
+        green: "#00aa00",
+        blue: "#0000aa",
+        yellow: "#aa5500",
+    };
+
+    pub fn color(&self, color: Color) -> &'static str {
+        match color {
+            Color::Default => self.default,
+            Color::White => self.white,
+            Color::Black => self.black,
+            Color::Red => self.red,
+            Color::Green => self.green,
+            Color::Blue => self.blue,
+            Color::Yellow => self.yellow,
+        }
+    }
+}
+
+impl std::default::Default for Palette {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+/// Gets the name of a color as used for CSS classes.
+fn class(color: Color) -> &'static str {
+    match color {
+        Color::Default => "default",
+        Color::White => "white",
+        Color::Black => "black",
+        Color::Red => "red",
+        Color::Green => "green",
+        Color::Blue => "blue",
+        Color::Yellow => "yellow",
+    }
+}
+
+const COLORS: [Color; 7] = [
+    Color::Default,
+    Color::White,
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Blue,
+    Color::Yellow,
+];
+
+fn escape(output: &mut String, character: char) {
+    match character {
+        '&' => output.push_str("&amp;"),
+        '<' => output.push_str("&lt;"),
+        '>' => output.push_str("&gt;"),
+        '"' => output.push_str("&quot;"),
+        character => output.push(character),
+    }
+}
+
+fn is_blank(pixel: &Pixel) -> bool {
+    pixel.is_transparent()
+        || pixel.is_continuation()
+        || (pixel.character == ' ' && pixel.marks.is_empty())
+}
+
+/// Characters of the same color written one after the other.
+struct Run {
+    x: usize,
+    columns: usize,
+    color: Color,
+    text: String,
+}
+
+/// Splits a row into runs of visible characters. Wide characters get a run of their own, so
+/// every glyph can be put into its cells regardless of the font.
+fn runs(row: &[Pixel]) -> Vec<Run> {
+    let mut runs: Vec<Run> = Vec::new();
+    let mut extendable = false;
+    for (x, pixel) in row.iter().enumerate() {
+        if is_blank(pixel) {
+            extendable = false;
+            continue;
+        }
+        match runs.last_mut() {
+            Some(run) if extendable && run.color == pixel.color && pixel.width == 1 => {
+                run.columns += 1;
+                run.text.push_str(&pixel.to_string());
+            }
+            _ => runs.push(Run {
+                x,
+                columns: pixel.width as usize,
+                color: pixel.color,
+                text: pixel.to_string(),
+            }),
+        }
+        extendable = pixel.width == 1;
+    }
+    runs
+}
+
+/// Exports the pixels as an SVG image.
+pub fn to_svg(pixels: &Matrix2<Pixel>, palette: &Palette) -> String {
+    let dimensions = pixels.dimensions();
+    let (width, height) = (
+        dimensions.x as f64 * CELL_WIDTH,
+        dimensions.y as f64 * CELL_HEIGHT,
+    );
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.1}\" height=\"{:.1}\" \
+         viewBox=\"0 0 {:.1} {:.1}\">",
+        width, height, width, height
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+        palette.background
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "<g font-family=\"{}\" font-size=\"{}\" xml:space=\"preserve\">",
+        FONT_FAMILY, FONT_SIZE
+    )
+    .unwrap();
+    for (y, row) in pixels.rows().enumerate() {
+        for run in runs(row) {
+            write!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{:.1}\" fill=\"{}\" textLength=\"{:.1}\" \
+                 lengthAdjust=\"spacingAndGlyphs\">",
+                run.x as f64 * CELL_WIDTH,
+                y as f64 * CELL_HEIGHT + BASELINE,
+                palette.color(run.color),
+                run.columns as f64 * CELL_WIDTH
+            )
+            .unwrap();
+            run.text.chars().for_each(|c| escape(&mut svg, c));
+            svg.push_str("</text>\n");
+        }
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+/// Exports the pixels as an HTML page showing them as preformatted text.
+pub fn to_html(pixels: &Matrix2<Pixel>, palette: &Palette) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>RustSnake</title>\n<style>\n");
+    writeln!(
+        html,
+        "pre {{ display: inline-block; margin: 0; padding: 1em; background: {}; color: {}; \
+         font-family: {}; line-height: 1.2; }}",
+        palette.background, palette.default, FONT_FAMILY
+    )
+    .unwrap();
+    for color in COLORS {
+        writeln!(
+            html,
+            ".{} {{ color: {}; }}",
+            class(color),
+            palette.color(color)
+        )
+        .unwrap();
+    }
+    html.push_str("</style>\n</head>\n<body>\n<pre>");
+    for row in pixels.rows() {
+        let mut color = None;
+        for pixel in row.iter().filter(|pixel| !pixel.is_continuation()) {
+            if is_blank(pixel) {
+                if color.take().is_some() {
+                    html.push_str("</span>");
+                }
+                html.push(' ');
+                continue;
+            }
+            if color != Some(pixel.color) {
+                if color.is_some() {
+                    html.push_str("</span>");
+                }
+                write!(html, "<span class=\"{}\">", class(pixel.color)).unwrap();
+                color = Some(pixel.color);
+            }
+            pixel.to_string().chars().for_each(|c| escape(&mut html, c));
+        }
+        if color.is_some() {
+            html.push_str("</span>");
+        }
+        html.push('\n');
+    }
+    html.push_str("</pre>\n</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Dimensions, Position};
+
+    fn pixels() -> Matrix2<Pixel> {
+        let mut pixels = Matrix2::new(&Dimensions::new(6, 2));
+        for (x, character) in "a<中 b".chars().enumerate() {
+            let x = if x > 2 { x + 1 } else { x };
+            pixels.set_pixel(&Position::new(x, 0), Pixel::new(character, Color::Red));
+        }
+        let mut c = Pixel::new('c', Color::Green);
+        c.marks.push('\u{327}');
+        pixels.set_pixel(&Position::new(5, 0), c);
+        pixels.set(0, 1, Pixel::TRANSPARENT);
+        pixels
+    }
+
+    #[test]
+    fn row_runs() {
+        let pixels = pixels();
+        let runs: Vec<_> = runs(pixels.row(0))
+            .into_iter()
+            .map(|run| (run.x, run.columns, run.color, run.text))
+            .collect();
+        assert_eq!(
+            runs,
+            [
+                (0, 2, Color::Red, "a<".to_string()),
+                (2, 2, Color::Red, "中".to_string()),
+                (5, 1, Color::Green, "c\u{327}".to_string()),
+            ]
+        );
+        assert!(super::runs(pixels.row(1)).is_empty());
+    }
+
+    #[test]
+    fn svg() {
+        let svg = to_svg(&pixels(), &Palette::DARK);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"50.4\""));
+        assert!(svg.contains(
+            "<text x=\"16.8\" y=\"13.0\" fill=\"#aa0000\" textLength=\"16.8\" \
+             lengthAdjust=\"spacingAndGlyphs\">中</text>"
+        ));
+        assert!(svg.contains(">a&lt;</text>"));
+        assert!(svg.contains("fill=\"#00aa00\" textLength=\"8.4\""));
+        assert_eq!(svg.matches("<text").count(), 3);
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn html() {
+        let html = to_html(&pixels(), &Palette::DARK);
+        assert!(html.contains(".red { color: #aa0000; }"));
+        assert!(html.contains(
+            "<pre><span class=\"red\">a&lt;中</span> <span class=\"green\">c\u{327}</span>\n      \n</pre>"
+        ));
+    }
+}
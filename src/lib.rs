@@ -1,5 +1,9 @@
+pub mod asciicast;
 pub mod cyclic_buffer;
 pub mod frame_buffer;
+pub mod occupancy;
 pub mod random;
+pub mod replay;
+pub mod scheduler;
 pub mod terminal;
 pub mod types;
@@ -1,8 +1,15 @@
+use rustsnake::asciicast;
 use rustsnake::cyclic_buffer;
-use rustsnake::frame_buffer::{Color, FrameBuffer, Pixel};
-use rustsnake::random;
+use rustsnake::frame_buffer::draw::Alignment;
+use rustsnake::frame_buffer::layer::{Layer, LayerId};
+use rustsnake::frame_buffer::screenshot::{self, Palette};
+use rustsnake::frame_buffer::{Color, FrameBuffer, Pixel, SwapStats};
+use rustsnake::occupancy::OccupancyGrid;
+use rustsnake::random::{self, EngineKind, Seed, SeedFromU64};
+use rustsnake::replay::{self, Event, Replay};
+use rustsnake::scheduler::{Scheduler, TickStats, TickTiming};
 use rustsnake::terminal;
-use rustsnake::types::{Dimensions, Position};
+use rustsnake::types::{Dimensions, Direction, Matrix2, Position, Rect};
 
 const FOOD_CHAR: char = 'x';
 const FOOD_COLOR: Color = Color::Green;
@@ -13,123 +20,234 @@ const WALL_COLOR: Color = Color::Yellow;
 
 const SNAKE_CHAR: char = '◉';
 const SNAKE_COLOR: Color = Color::Blue;
+const SNAKE_INITIAL_CAPACITY: usize = 16;
 
 const SCORE_COLOR: Color = Color::Red;
 const SPEED_COLOR: Color = SCORE_COLOR;
+const OVERLAY_COLOR: Color = Color::White;
+const NOTICE_COLOR: Color = Color::White;
+const NOTICE_DURATION: std::time::Duration = std::time::Duration::from_secs(3);
 
-fn draw_border(dimensions: &Dimensions, frame_buffer: &mut FrameBuffer) {
-    let back_buffer = frame_buffer.back_buffer();
+struct Options {
+    timing: TickTiming,
+    render_step: Option<std::time::Duration>,
+    rng: random::EngineKind,
+    seed: Option<Seed>,
+    asciicast: Option<std::path::PathBuf>,
+    save_replay: Option<std::path::PathBuf>,
+    replay: Option<std::path::PathBuf>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            timing: TickTiming::Uniform,
+            render_step: None,
+            rng: random::EngineKind::default(),
+            seed: None,
+            asciicast: None,
+            save_replay: None,
+            replay: None,
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--aspect-ratio" => {
+                    let value = args.next().ok_or("--aspect-ratio requires a value")?;
+                    let vertical_ratio: f64 = value
+                        .parse()
+                        .map_err(|_| format!("invalid aspect ratio: {}", value))?;
+                    if !(vertical_ratio.is_finite() && vertical_ratio > 0.0) {
+                        return Err(format!("invalid aspect ratio: {}", value));
+                    }
+                    options.timing = TickTiming::AspectCorrected { vertical_ratio };
+                }
+                "--fps" => {
+                    let value = args.next().ok_or("--fps requires a value")?;
+                    let fps: u32 = value
+                        .parse()
+                        .ok()
+                        .filter(|fps| *fps > 0)
+                        .ok_or_else(|| format!("invalid frame rate: {}", value))?;
+                    options.render_step = Some(std::time::Duration::from_secs(1) / fps);
+                }
+                "--rng" => {
+                    let value = args.next().ok_or("--rng requires a value")?;
+                    options.rng = value.parse()?;
+                }
+                "--asciicast" => {
+                    let value = args.next().ok_or("--asciicast requires a value")?;
+                    options.asciicast = Some(value.into());
+                }
+                "--save-replay" => {
+                    let value = args.next().ok_or("--save-replay requires a value")?;
+                    options.save_replay = Some(value.into());
+                }
+                "--replay" => {
+                    let value = args.next().ok_or("--replay requires a value")?;
+                    options.replay = Some(value.into());
+                }
+                "--seed" => {
+                    let value = args.next().ok_or("--seed requires a value")?;
+                    options.seed = Some(value.parse()?);
+                }
+                _ => return Err(format!("unknown argument: {}", arg)),
+            }
+        }
+        if options.replay.is_some() && options.asciicast.is_none() {
+            return Err("--replay requires --asciicast".to_string());
+        }
+        Ok(options)
+    }
+}
+
+fn draw_border(dimensions: &Dimensions, pixels: &mut Matrix2<Pixel>) {
+    let wall = Pixel::new(WALL_CHAR, WALL_COLOR);
     for x in 0..dimensions.x {
-        back_buffer.set(
-            x,
-            0,
-            Pixel {
-                character: WALL_CHAR,
-                color: WALL_COLOR,
-            },
-        );
-        back_buffer.set(
-            x,
-            dimensions.y - 1,
-            Pixel {
-                character: WALL_CHAR,
-                color: WALL_COLOR,
-            },
-        );
+        pixels.set_pixel(&Position::new(x, 0), wall);
+        pixels.set_pixel(&Position::new(x, dimensions.y - 1), wall);
     }
     for y in 1..dimensions.y - 1 {
-        back_buffer.set(
-            0,
-            y,
-            Pixel {
-                character: WALL_CHAR,
-                color: WALL_COLOR,
-            },
-        );
-        back_buffer.set(
-            dimensions.x - 1,
-            y,
-            Pixel {
-                character: WALL_CHAR,
-                color: WALL_COLOR,
-            },
-        );
+        pixels.set_pixel(&Position::new(0, y), wall);
+        pixels.set_pixel(&Position::new(dimensions.x - 1, y), wall);
     }
 }
 
 fn draw_score(score: usize, dimensions: &Dimensions, frame_buffer: &mut FrameBuffer) {
-    let back_buffer = frame_buffer.back_buffer();
-    for (i, character) in format!("Score: {}", score).chars().enumerate() {
-        back_buffer.set(
-            i + 1,
-            dimensions.y - 1,
-            Pixel {
-                character,
-                color: SCORE_COLOR,
-            },
-        );
-    }
+    frame_buffer.canvas().print(
+        Position::new(1, dimensions.y - 1),
+        &format!("Score: {}", score),
+        SCORE_COLOR,
+    );
 }
 
 fn draw_speed(speed: usize, dimensions: &Dimensions, frame_buffer: &mut FrameBuffer) {
-    let back_buffer = frame_buffer.back_buffer();
-    for (i, character) in format!("Speed: {}", speed).chars().rev().enumerate() {
-        back_buffer.set(
-            dimensions.x - i - 2,
-            dimensions.y - 1,
-            Pixel {
-                character,
-                color: SPEED_COLOR,
-            },
-        );
-    }
+    let area = Rect::new(
+        Position::new(1, dimensions.y - 1),
+        Dimensions::new(dimensions.x - 2, 1),
+    );
+    frame_buffer.canvas().draw_text(
+        &area,
+        &format!("Speed: {}", speed),
+        SPEED_COLOR,
+        Alignment::Right,
+    );
 }
 
-#[derive(PartialEq, Clone, Copy)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+fn draw_overlay(
+    tick_stats: &TickStats,
+    swap_stats: &SwapStats,
+    dimensions: &Dimensions,
+    layer: &mut Layer,
+) {
+    let text = format!(
+        " tick {:.1}ms late {:.1}ms overruns {} | {} B {} cells ",
+        tick_stats.interval.as_secs_f64() * 1000.0,
+        tick_stats.lateness.as_secs_f64() * 1000.0,
+        tick_stats.overruns,
+        swap_stats.bytes_written,
+        swap_stats.dirty_cells,
+    );
+    let area = Rect::new(Position::new(1, 0), Dimensions::new(dimensions.x - 2, 1));
+    layer.clear();
+    layer
+        .canvas()
+        .draw_text(&area, &text, OVERLAY_COLOR, Alignment::Left);
 }
 
-impl Direction {
-    fn is_opposite(&self, other: Direction) -> bool {
-        *self == Self::Up && other == Self::Down
-            || *self == Self::Down && other == Self::Up
-            || *self == Self::Left && other == Self::Right
-            || *self == Self::Right && other == Self::Left
-    }
+/// Shows a message centered in the status line.
+fn draw_notice(message: &str, dimensions: &Dimensions, layer: &mut Layer) {
+    let area = Rect::new(
+        Position::new(1, dimensions.y - 1),
+        Dimensions::new(dimensions.x - 2, 1),
+    );
+    layer.clear();
+    layer
+        .canvas()
+        .draw_text(&area, message, NOTICE_COLOR, Alignment::Center);
+    layer.set_visible(true);
+}
+
+/// Saves the pixels as SVG and HTML files in the current directory and returns their name
+/// without the extension.
+fn save_screenshot(pixels: &Matrix2<Pixel>) -> std::io::Result<String> {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    let name = format!("rustsnake-{}", timestamp.as_millis());
+    let palette = Palette::default();
+    std::fs::write(
+        format!("{}.svg", name),
+        screenshot::to_svg(pixels, &palette),
+    )?;
+    std::fs::write(
+        format!("{}.html", name),
+        screenshot::to_html(pixels, &palette),
+    )?;
+    Ok(name)
 }
 
 struct Snake {
     segments: cyclic_buffer::CyclicBuffer<Position>,
+    /// Cells covered by the walls and the segments, updated on every tick.
+    occupancy: OccupancyGrid,
+    collided: bool,
+    max_segments: usize,
     pub direction: Direction,
+    heading: Direction,
     score: usize,
 }
 
 impl Snake {
     fn new(dimensions: &Dimensions) -> Self {
-        let mut segments = cyclic_buffer::CyclicBuffer::new(Self::max_segments(dimensions));
-        segments.push(Position {
+        let mut segments = cyclic_buffer::CyclicBuffer::with_growth_policy(
+            SNAKE_INITIAL_CAPACITY,
+            cyclic_buffer::GrowthPolicy::Double,
+        );
+        let head = Position {
             x: dimensions.x / 2,
             y: dimensions.y / 2,
-        });
+        };
+        let mut occupancy = OccupancyGrid::new(dimensions);
+        for x in 0..dimensions.x {
+            occupancy.occupy(&Position { x, y: 0 });
+            occupancy.occupy(&Position {
+                x,
+                y: dimensions.y - 1,
+            });
+        }
+        for y in 0..dimensions.y {
+            occupancy.occupy(&Position { x: 0, y });
+            occupancy.occupy(&Position {
+                x: dimensions.x - 1,
+                y,
+            });
+        }
+        occupancy.occupy(&head);
+        segments.push(head);
         Self {
             segments,
+            occupancy,
+            collided: false,
+            max_segments: Self::max_segments(dimensions),
             direction: Direction::Right,
+            heading: Direction::Right,
             score: 0,
         }
     }
 
-    pub fn segments(&self) -> cyclic_buffer::Iter<'_, Position> {
-        self.segments.iter()
+    fn occupancy(&self) -> &OccupancyGrid {
+        &self.occupancy
     }
 
     fn score(&self) -> usize {
         self.score
     }
 
+    /// Direction of the last move, which may differ from `direction` between ticks.
+    fn heading(&self) -> Direction {
+        self.heading
+    }
+
     fn max_segments(dimensions: &Dimensions) -> usize {
         (dimensions.x - 2) * (dimensions.y - 2)
     }
@@ -137,66 +255,41 @@ impl Snake {
     fn draw(&self, frame_buffer: &mut FrameBuffer) {
         let back_buffer = frame_buffer.back_buffer();
         for segment in self.segments.iter() {
-            back_buffer.set(
-                segment.x,
-                segment.y,
-                Pixel {
-                    character: SNAKE_CHAR,
-                    color: SNAKE_COLOR,
-                },
-            );
+            back_buffer.set_pixel(segment, Pixel::new(SNAKE_CHAR, SNAKE_COLOR));
         }
     }
 
     fn won(&self) -> bool {
-        self.segments.full()
+        self.segments.count() == self.max_segments
     }
 
     fn tick(&mut self, food: &Food) -> bool {
-        let head = self.segments.iter().last().unwrap();
-        let new_head = match self.direction {
-            Direction::Up => Position {
-                x: head.x,
-                y: head.y - 1,
-            },
-            Direction::Down => Position {
-                x: head.x,
-                y: head.y + 1,
-            },
-            Direction::Left => Position {
-                x: head.x - 1,
-                y: head.y,
-            },
-            Direction::Right => Position {
-                x: head.x + 1,
-                y: head.y,
-            },
+        let head = self.segments.back().unwrap();
+        self.heading = self.direction;
+        let Some(new_head) =
+            head.checked_offset(self.direction.offset(), self.occupancy.dimensions())
+        else {
+            self.collided = true;
+            return false;
         };
         let eat = self.eat(food);
         if eat {
             self.score += FOOD_SCORE;
-        } else {
-            self.segments.pop();
+        } else if let Some(tail) = self.segments.pop() {
+            self.occupancy.vacate(&tail);
         }
+        self.collided = !self.occupancy.occupy(&new_head);
         self.segments.push(new_head);
         eat
     }
 
-    fn alive(&self, dimensions: &Dimensions) -> bool {
-        let head = self.segments.iter().last().unwrap();
-        let head_id = self.segments.count() - 1;
-        let hit_wall =
-            head.x < 1 || head.x >= dimensions.x - 1 || head.y < 1 || head.y >= dimensions.y - 1;
-        let bit_self = self
-            .segments
-            .iter()
-            .enumerate()
-            .any(|(id, x)| x == head && id != head_id);
-        !hit_wall && !bit_self
+    /// Whether the last tick moved the head into a wall or another segment.
+    fn alive(&self) -> bool {
+        !self.collided
     }
 
     fn eat(&self, food: &Food) -> bool {
-        let head = self.segments.iter().last().unwrap();
+        let head = self.segments.back().unwrap();
         *head == food.position
     }
 }
@@ -206,106 +299,401 @@ struct Food {
 }
 
 impl Food {
-    fn new<'a, T: random::RandomNumberEngine>(
-        dimensions: &Dimensions,
-        rng: &mut T,
-        blocked_fields: &mut (impl core::iter::Iterator<Item = &'a Position> + Clone),
-    ) -> Self
-    where
-        u32: From<<T as random::RandomNumberEngine>::ResultType>,
-    {
-        let fields_total = (dimensions.x - 2) * (dimensions.y - 2);
-        let rand: u32 = rng.get().into();
-        let rand: usize = rand as usize % (fields_total - blocked_fields.clone().count());
-        let mut free_fields: Vec<Position> = Vec::new();
-        free_fields.reserve_exact(fields_total);
-        for y in 1..dimensions.y - 1 {
-            for x in 1..dimensions.x - 1 {
-                let position = Position { x, y };
-                if !blocked_fields.clone().any(|x| *x == position) {
-                    free_fields.push(Position { x, y });
-                }
-            }
-        }
-        Self {
-            position: free_fields.into_iter().nth(rand).unwrap(),
-        }
+    /// Places food on a random free cell, or returns `None` if there is none left.
+    fn new(
+        rng: &mut impl random::RandomNumberEngineExt,
+        occupancy: &OccupancyGrid,
+    ) -> Option<Self> {
+        occupancy.choose_free(rng).map(|position| Self {
+            position: *position,
+        })
     }
 
     fn draw(&self, frame_buffer: &mut FrameBuffer) {
         let back_buffer = frame_buffer.back_buffer();
-        back_buffer.set(
-            self.position.x,
-            self.position.y,
-            Pixel {
-                character: FOOD_CHAR,
-                color: FOOD_COLOR,
-            },
-        );
+        back_buffer.set_pixel(&self.position, Pixel::new(FOOD_CHAR, FOOD_COLOR));
     }
 }
 
-fn get_direction_from_stdin(rx: &std::sync::mpsc::Receiver<u8>) -> Option<Direction> {
-    let mut direction: Option<Direction> = None;
+#[derive(Default)]
+struct Input {
+    direction: Option<Direction>,
+    toggle_overlay: bool,
+    screenshot: bool,
+    quit: bool,
+}
+
+fn parse_input(bytes: &[u8]) -> Input {
+    let mut input = Input::default();
 
-    for byte in rx.try_iter() {
+    for byte in bytes {
         match byte {
-            b'w' => direction = Some(Direction::Up),
-            b's' => direction = Some(Direction::Down),
-            b'a' => direction = Some(Direction::Left),
-            b'd' => direction = Some(Direction::Right),
+            b'w' => input.direction = Some(Direction::Up),
+            b's' => input.direction = Some(Direction::Down),
+            b'a' => input.direction = Some(Direction::Left),
+            b'd' => input.direction = Some(Direction::Right),
+            b'i' => input.toggle_overlay = !input.toggle_overlay,
+            b'e' => input.screenshot = true,
+            // Ctrl-C does not raise SIGINT in raw mode.
+            b'q' | 0x03 => input.quit = true,
             _ => {}
         }
     }
-    direction
+    input
 }
 
-fn main() {
-    terminal::set_mode(false);
-    terminal::reset();
-    terminal::hide_cursor();
-    let dimensions = terminal::get_dimensions().unwrap();
-    let field_dimensions = Dimensions {
-        x: dimensions.x,
-        y: dimensions.y - 1,
-    };
-    let mut rng = random::PCG32Fast::new(None);
-    let (tx, rx) = std::sync::mpsc::channel();
-    std::thread::spawn(move || {
-        use std::io::Read;
-        let mut stdin = std::io::stdin();
-        let mut buffer = [0u8; 1];
-        loop {
-            stdin.read_exact(&mut buffer).unwrap();
-            tx.send(*buffer.first().unwrap()).unwrap();
+/// The state of a game, driven either live by the terminal or by the events of a replay.
+struct Game<R> {
+    dimensions: Dimensions,
+    rng: R,
+    frame_buffer: FrameBuffer,
+    overlay: LayerId,
+    notice: LayerId,
+    /// Time since the start at which the notice is hidden again.
+    notice_until: Option<std::time::Duration>,
+    snake: Snake,
+    food: Food,
+    speed: usize,
+    show_overlay: bool,
+}
+
+impl<R: random::RandomNumberEngineExt + SeedFromU64> Game<R> {
+    fn new(
+        seed: Seed,
+        dimensions: &Dimensions,
+        capabilities: terminal::Capabilities,
+    ) -> std::io::Result<Self> {
+        let dimensions = *dimensions;
+        let field_dimensions = Dimensions {
+            x: dimensions.x,
+            y: dimensions.y - 1,
+        };
+        let mut rng = R::seed_from_u64(seed.0);
+        let mut frame_buffer = FrameBuffer::with_capabilities(&dimensions, capabilities);
+        // The walls never change and are drawn once.
+        let walls = frame_buffer.add_layer("walls", 0);
+        draw_border(
+            &field_dimensions,
+            frame_buffer.layer_mut(walls).pixels_mut(),
+        );
+        let overlay = frame_buffer.add_layer("overlay", 1);
+        let notice = frame_buffer.add_layer("notice", 2);
+        let snake = Snake::new(&field_dimensions);
+        let food = Food::new(&mut rng, snake.occupancy())
+            .ok_or_else(|| std::io::Error::other("the terminal is too small"))?;
+        Ok(Self {
+            dimensions,
+            rng,
+            frame_buffer,
+            overlay,
+            notice,
+            notice_until: None,
+            snake,
+            food,
+            speed: 0,
+            show_overlay: false,
+        })
+    }
+
+    fn step(&self, timing: &TickTiming) -> std::time::Duration {
+        timing.step(
+            std::time::Duration::from_millis(100 - self.speed as u64),
+            self.snake.direction.is_vertical(),
+        )
+    }
+
+    /// Moves the snake and returns whether the game is over.
+    fn tick(&mut self) -> bool {
+        if self.snake.tick(&self.food) {
+            // No food is left to place once the snake covers the whole field and has won.
+            if let Some(food) = Food::new(&mut self.rng, self.snake.occupancy()) {
+                self.food = food;
+            }
+            self.speed = std::cmp::min(self.speed + 5, 50);
         }
-    });
+        !self.snake.alive() || self.snake.won()
+    }
 
-    let mut frame_buffer = FrameBuffer::new(&dimensions);
-    let mut snake = Snake::new(&field_dimensions);
-    let mut food = Food::new(&field_dimensions, &mut rng, &mut snake.segments());
-    let mut speed = 0;
+    /// Draws the frame shown at the given time since the start into the back buffer.
+    fn draw(&mut self, time: std::time::Duration, tick_stats: &TickStats) {
+        let dimensions = self.dimensions;
+        let frame_buffer = &mut self.frame_buffer;
+        draw_score(self.snake.score(), &dimensions, frame_buffer);
+        draw_speed(self.speed, &dimensions, frame_buffer);
+        self.snake.draw(frame_buffer);
+        self.food.draw(frame_buffer);
+        if self.notice_until.is_some_and(|until| time >= until) {
+            frame_buffer.layer_mut(self.notice).set_visible(false);
+            self.notice_until = None;
+        }
+        frame_buffer
+            .layer_mut(self.overlay)
+            .set_visible(self.show_overlay);
+        if self.show_overlay {
+            let swap_stats = *frame_buffer.swap_stats();
+            draw_overlay(
+                tick_stats,
+                &swap_stats,
+                &dimensions,
+                frame_buffer.layer_mut(self.overlay),
+            );
+        }
+    }
+
+    /// Applies the keys that change the game. Quitting and screenshots are up to the caller.
+    fn steer(&mut self, input: &Input) {
+        if let Some(new_direction) = input.direction {
+            if !new_direction.is_opposite(self.snake.heading()) {
+                self.snake.direction = new_direction;
+            }
+        }
+        self.show_overlay ^= input.toggle_overlay;
+    }
+
+    fn show_notice(&mut self, message: &str, time: std::time::Duration) {
+        draw_notice(
+            message,
+            &self.dimensions,
+            self.frame_buffer.layer_mut(self.notice),
+        );
+        self.notice_until = Some(time + NOTICE_DURATION);
+    }
+}
+
+fn save_event(
+    replay: &mut Option<replay::Writer<impl std::io::Write>>,
+    time: std::time::Duration,
+    event: &Event,
+) -> std::io::Result<()> {
+    match replay {
+        Some(replay) => replay.event(time, event),
+        None => Ok(()),
+    }
+}
+
+/// Drops the nanoseconds, since replays are saved with microsecond precision.
+fn whole_micros(duration: std::time::Duration) -> std::time::Duration {
+    std::time::Duration::from_micros(duration.as_micros() as u64)
+}
+
+fn create_file(path: &std::path::Path) -> std::io::Result<std::io::BufWriter<std::fs::File>> {
+    Ok(std::io::BufWriter::new(std::fs::File::create(path)?))
+}
+
+fn play<R: random::RandomNumberEngineExt + SeedFromU64>(
+    options: &Options,
+    dimensions: &Dimensions,
+    seed: Seed,
+) -> std::io::Result<usize> {
+    let dimensions = *dimensions;
+    let mut terminal_input = terminal::Input::new();
+
+    let mut capabilities = terminal::Capabilities::detect();
+    capabilities.synchronized_output = terminal::query_synchronized_output(
+        &mut terminal_input,
+        std::time::Duration::from_millis(250),
+    )?;
+    let mut game = Game::<R>::new(seed, &dimensions, capabilities)?;
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|timestamp| timestamp.as_secs());
+    let mut recorder = match &options.asciicast {
+        Some(path) => Some(asciicast::Recorder::new(
+            create_file(path)?,
+            &dimensions,
+            timestamp,
+        )?),
+        None => None,
+    };
+    let mut replay = match &options.save_replay {
+        Some(path) => {
+            let header = replay::Header {
+                rng: options.rng,
+                seed,
+                dimensions,
+                capabilities,
+                timestamp,
+            };
+            Some(replay::Writer::new(create_file(path)?, &header)?)
+        }
+        None => None,
+    };
+    let start = std::time::Instant::now();
+    let mut scheduler = Scheduler::new(start);
+    let mut render_scheduler = Scheduler::new(start);
     loop {
-        if snake.tick(&food) {
-            food = Food::new(&field_dimensions, &mut rng, &mut snake.segments());
-            speed = std::cmp::min(speed + 5, 50);
+        let step = game.step(&options.timing);
+        let now = std::time::Instant::now();
+        let time = whole_micros(now - start);
+        let ticked = scheduler.poll(now, step);
+        let mut game_over = false;
+        if ticked {
+            save_event(&mut replay, time, &Event::Tick)?;
+            game_over = game.tick();
         }
-        draw_border(&field_dimensions, &mut frame_buffer);
-        draw_score(snake.score(), &dimensions, &mut frame_buffer);
-        draw_speed(speed, &dimensions, &mut frame_buffer);
-        snake.draw(&mut frame_buffer);
-        let new_direction = get_direction_from_stdin(&rx).unwrap_or(snake.direction);
-        if !new_direction.is_opposite(snake.direction) {
-            snake.direction = new_direction;
+        let render = match options.render_step {
+            Some(render_step) => render_scheduler.poll(now, render_step),
+            None => ticked,
+        };
+        if render || game_over {
+            let tick_stats = TickStats {
+                interval: whole_micros(scheduler.stats().interval),
+                lateness: whole_micros(scheduler.stats().lateness),
+                ..*scheduler.stats()
+            };
+            save_event(&mut replay, time, &Event::Render(tick_stats))?;
+            game.draw(time, &tick_stats);
+            game.frame_buffer.swap_buffers()?;
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.output(time, game.frame_buffer.last_output())?;
+            }
+        }
+        if game_over {
+            break;
+        }
+        let now = std::time::Instant::now();
+        let mut timeout = scheduler.remaining(now, step);
+        if let Some(render_step) = options.render_step {
+            timeout = std::cmp::min(timeout, render_scheduler.remaining(now, render_step));
+        }
+        let bytes = match terminal_input.read(Some(timeout)) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(error) => return Err(error),
+        };
+        if !bytes.is_empty() {
+            let time = whole_micros(start.elapsed());
+            save_event(&mut replay, time, &Event::Input(bytes.to_vec()))?;
         }
-        food.draw(&mut frame_buffer);
-        frame_buffer.swap_buffers();
-        std::thread::sleep(std::time::Duration::from_millis(100 - speed as u64));
-        if !snake.alive(&field_dimensions) || snake.won() {
+        let input = parse_input(bytes);
+        if input.quit {
             break;
         }
+        game.steer(&input);
+        if input.screenshot {
+            let message = match save_screenshot(game.frame_buffer.front_buffer()) {
+                Ok(name) => format!(" Saved {}.svg and .html ", name),
+                Err(error) => format!(" Screenshot failed: {} ", error),
+            };
+            let time = whole_micros(start.elapsed());
+            save_event(&mut replay, time, &Event::Notice(message.clone()))?;
+            game.show_notice(&message, time);
+        }
+    }
+    if let Some(recorder) = recorder {
+        recorder.finish()?;
+    }
+    if let Some(replay) = replay {
+        replay.finish()?;
+    }
+    Ok(game.snake.score())
+}
+
+/// Re-simulates a saved game without a terminal and records its frames with the times they were
+/// shown at, as fast as they can be rendered.
+fn render_replay<R: random::RandomNumberEngineExt + SeedFromU64>(
+    replay: &Replay,
+    asciicast: &std::path::Path,
+) -> std::io::Result<usize> {
+    let header = &replay.header;
+    let mut game = Game::<R>::new(header.seed, &header.dimensions, header.capabilities)?;
+    let mut recorder = asciicast::Recorder::new(
+        create_file(asciicast)?,
+        &header.dimensions,
+        header.timestamp,
+    )?;
+    for (time, event) in &replay.events {
+        match event {
+            Event::Input(bytes) => {
+                let input = parse_input(bytes);
+                if input.quit {
+                    break;
+                }
+                // Screenshots are not taken again. Their notices are events of their own.
+                game.steer(&input);
+            }
+            Event::Tick => {
+                game.tick();
+            }
+            Event::Render(tick_stats) => {
+                game.draw(*time, tick_stats);
+                game.frame_buffer.swap_buffers_to(&mut std::io::sink())?;
+                recorder.output(*time, game.frame_buffer.last_output())?;
+            }
+            Event::Notice(message) => game.show_notice(message, *time),
+        }
+    }
+    recorder.finish()?;
+    Ok(game.snake.score())
+}
+
+/// Plays a game in the terminal, or renders a saved one if a replay is given.
+fn run<R: random::RandomNumberEngineExt + SeedFromU64>(
+    options: &Options,
+    seed: Seed,
+    replay: Option<&Replay>,
+) -> std::io::Result<usize> {
+    if let Some(replay) = replay {
+        // Options::parse makes sure an asciicast is given along with a replay.
+        let asciicast = options.asciicast.as_deref().unwrap();
+        return render_replay::<R>(replay, asciicast);
+    }
+    let dimensions = terminal::get_dimensions()?;
+    let raw_mode = terminal::RawMode::enable()?;
+    let alternate_screen = terminal::AlternateScreen::enter()?;
+    let result = play::<R>(options, &dimensions, seed);
+    drop(alternate_screen);
+    drop(raw_mode);
+    result
+}
+
+fn load_replay(path: &std::path::Path) -> Result<Replay, String> {
+    std::fs::read_to_string(path)
+        .map_err(|error| error.to_string())
+        .and_then(|text| text.parse())
+        .map_err(|error| format!("{}: {}", path.display(), error))
+}
+
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
+    let replay = match options.replay.as_deref().map(load_replay).transpose() {
+        Ok(replay) => replay,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+    let (rng, seed) = match &replay {
+        Some(replay) => (replay.header.rng, replay.header.seed),
+        None => (options.rng, options.seed.unwrap_or_else(Seed::from_entropy)),
+    };
+    let replay = replay.as_ref();
+    let result = match rng {
+        EngineKind::PCG32Fast => run::<random::PCG32Fast>(&options, seed, replay),
+        EngineKind::PCG32FastRxsMXs => run::<random::PCG32FastRxsMXs>(&options, seed, replay),
+        EngineKind::PCG32 => run::<random::PCG32>(&options, seed, replay),
+        EngineKind::PCG64 => run::<random::PCG64>(&options, seed, replay),
+        EngineKind::PCG64Fast => run::<random::PCG64Fast>(&options, seed, replay),
+        EngineKind::SplitMix64 => run::<random::SplitMix64>(&options, seed, replay),
+        EngineKind::Xoshiro256StarStar => run::<random::Xoshiro256StarStar>(&options, seed, replay),
+        EngineKind::Xoroshiro128Plus => run::<random::Xoroshiro128Plus>(&options, seed, replay),
+        EngineKind::WyRand => run::<random::WyRand>(&options, seed, replay),
+    };
+    match result {
+        Ok(score) => {
+            println!("Final score: {}", score);
+            println!("Seed: {} (replay with --seed {})", seed, seed);
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
     }
-    terminal::set_mode(true);
-    terminal::reset();
-    println!("Final score: {}", snake.score());
 }
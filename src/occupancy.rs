@@ -0,0 +1,146 @@
+use crate::random::RandomNumberEngineExt;
+use crate::types::{Dimensions, Matrix2, Position};
+
+/// Tracks which cells of a grid are occupied, e.g. by walls or snake segments.
+///
+/// Besides answering whether a cell is occupied in O(1), it maintains the set of free cells so
+/// that a random free cell can be chosen in O(1) as well, without scanning the whole grid.
+pub struct OccupancyGrid {
+    /// Index of every free cell into `free_cells`, or `None` if the cell is occupied.
+    slots: Matrix2<Option<usize>>,
+    free_cells: Vec<Position>,
+}
+
+impl OccupancyGrid {
+    /// Constructs a grid with all cells free.
+    pub fn new(dimensions: &Dimensions) -> Self {
+        let mut slots = Matrix2::new(dimensions);
+        let mut free_cells = Vec::with_capacity(dimensions.x * dimensions.y);
+        for y in 0..dimensions.y {
+            for x in 0..dimensions.x {
+                slots.set(x, y, Some(free_cells.len()));
+                free_cells.push(Position { x, y });
+            }
+        }
+        Self { slots, free_cells }
+    }
+
+    pub fn dimensions(&self) -> &Dimensions {
+        self.slots.dimensions()
+    }
+
+    pub fn occupied(&self, position: &Position) -> bool {
+        self.slots.get(position.x, position.y).is_none()
+    }
+
+    /// Gets the number of free cells.
+    pub fn free_count(&self) -> usize {
+        self.free_cells.len()
+    }
+
+    /// Gets the free cells in no particular order.
+    pub fn free_cells(&self) -> &[Position] {
+        &self.free_cells
+    }
+
+    /// Marks a cell as occupied. Returns `false` if it already was.
+    pub fn occupy(&mut self, position: &Position) -> bool {
+        let Some(index) = *self.slots.get(position.x, position.y) else {
+            return false;
+        };
+        self.slots.set(position.x, position.y, None);
+        self.free_cells.swap_remove(index);
+        if let Some(moved) = self.free_cells.get(index) {
+            self.slots.set(moved.x, moved.y, Some(index));
+        }
+        true
+    }
+
+    /// Marks a cell as free. Returns `false` if it already was.
+    pub fn vacate(&mut self, position: &Position) -> bool {
+        if !self.occupied(position) {
+            return false;
+        }
+        self.slots
+            .set(position.x, position.y, Some(self.free_cells.len()));
+        self.free_cells.push(*position);
+        true
+    }
+
+    /// Chooses a free cell uniformly at random, or `None` if all cells are occupied.
+    pub fn choose_free(&self, rng: &mut impl RandomNumberEngineExt) -> Option<&Position> {
+        rng.choose(&self.free_cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::PCG32Fast;
+
+    /// Checks the grid against a full scan of all cells.
+    fn assert_consistent(grid: &OccupancyGrid, dimensions: &Dimensions) {
+        let mut expected = Vec::new();
+        for y in 0..dimensions.y {
+            for x in 0..dimensions.x {
+                let position = Position { x, y };
+                if !grid.occupied(&position) {
+                    expected.push(position);
+                }
+            }
+        }
+        let mut free_cells = grid.free_cells().to_vec();
+        free_cells.sort_by_key(|position| (position.y, position.x));
+        assert_eq!(free_cells, expected);
+    }
+
+    #[test]
+    fn occupy_and_vacate() {
+        let dimensions = Dimensions { x: 4, y: 3 };
+        let mut grid = OccupancyGrid::new(&dimensions);
+        assert_eq!(grid.free_count(), 12);
+        let position = Position { x: 1, y: 2 };
+        assert!(grid.occupy(&position));
+        assert!(!grid.occupy(&position));
+        assert!(grid.occupied(&position));
+        assert_eq!(grid.free_count(), 11);
+        assert_consistent(&grid, &dimensions);
+        assert!(grid.vacate(&position));
+        assert!(!grid.vacate(&position));
+        assert_eq!(grid.free_count(), 12);
+        assert_consistent(&grid, &dimensions);
+    }
+
+    #[test]
+    fn random_operations_stay_consistent() {
+        let dimensions = Dimensions { x: 7, y: 5 };
+        let mut grid = OccupancyGrid::new(&dimensions);
+        let mut rng = PCG32Fast::new(None);
+        for _ in 0..1000 {
+            let position = Position {
+                x: rng.gen_below(7) as usize,
+                y: rng.gen_below(5) as usize,
+            };
+            let occupied = grid.occupied(&position);
+            match rng.gen_bool(0.5) {
+                true => assert_eq!(grid.occupy(&position), !occupied),
+                false => assert_eq!(grid.vacate(&position), occupied),
+            }
+            assert_consistent(&grid, &dimensions);
+        }
+    }
+
+    #[test]
+    fn choose_free() {
+        let dimensions = Dimensions { x: 3, y: 1 };
+        let mut grid = OccupancyGrid::new(&dimensions);
+        let mut rng = PCG32Fast::new(None);
+        grid.occupy(&Position { x: 0, y: 0 });
+        grid.occupy(&Position { x: 2, y: 0 });
+        for _ in 0..10 {
+            assert_eq!(grid.choose_free(&mut rng), Some(&Position { x: 1, y: 0 }));
+        }
+        grid.occupy(&Position { x: 1, y: 0 });
+        assert_eq!(grid.choose_free(&mut rng), None);
+    }
+}
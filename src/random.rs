@@ -18,167 +18,129 @@ pub trait RandomNumberEngine: PartialEq {
     const MAX: Self::ResultType;
 }
 
-/// Random number engine for 32 bit random numbers.
-///
-/// This is a minimal implementation of the pcg32_fast PRNG from <https://www.pcg-random.org>.
-/// The internal state has 64 bit, so there are no 128 bit operations required.
-///
-/// # TODO
-///
-/// * Implement 64 bit generator if required.
-#[derive(Debug)]
-pub struct PCG32Fast {
-    state: <Self as RandomNumberEngine>::StateType,
+/// Engines that can be constructed from a single 64 bit seed, e.g. one shared between players.
+pub trait SeedFromU64: RandomNumberEngine + Sized {
+    /// Constructs the engine from `seed`, expanding it to the full state if necessary.
+    fn seed_from_u64(seed: u64) -> Self;
 }
 
-impl RandomNumberEngine for PCG32Fast {
-    type ResultType = u32;
-    type StateType = u64;
-
-    const MIN: Self::ResultType = Self::ResultType::MIN;
-    const MAX: Self::ResultType = Self::ResultType::MAX;
-
-    fn seed(&mut self, seed: Option<Self::StateType>) {
-        self.state = Self::mangle_seed(seed);
-    }
-
-    fn state(&self) -> Self::StateType {
-        self.state
-    }
-
-    fn get(&mut self) -> Self::ResultType {
-        let old_state = self.state;
-        self.advance();
-        Self::output(old_state)
-    }
-
-    fn discard(&mut self, z: usize) {
-        for _ in 0..z {
-            self.advance();
-        }
-        // TODO: self.state *= integral_pow_overflow(Self::MULTIPLIER, z);
-    }
+pub mod checkpoint;
+pub mod distributions;
+pub mod pcg;
+pub mod seed;
+pub mod splitmix;
+pub mod wyrand;
+pub mod xoshiro;
+
+pub use checkpoint::{Checkpoint, CheckpointError};
+pub use distributions::RandomNumberEngineExt;
+pub use pcg::{PCG32Fast, PCG32FastRxsMXs, PCG64Fast, PCG32, PCG64};
+pub use seed::Seed;
+pub use splitmix::SplitMix64;
+pub use wyrand::WyRand;
+pub use xoshiro::{Xoroshiro128Plus, Xoshiro256StarStar};
+
+/// Seed used by all engines when none is given.
+const DEFAULT_SEED: u64 = 0xcafef00dd15ea5e5;
+
+/// Names the available engines, e.g. to select one on the command line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EngineKind {
+    #[default]
+    PCG32Fast,
+    PCG32FastRxsMXs,
+    PCG32,
+    PCG64,
+    PCG64Fast,
+    SplitMix64,
+    Xoshiro256StarStar,
+    Xoroshiro128Plus,
+    WyRand,
 }
 
-// NOTE: This could be improved with inherent_associated_types which is not implemented yet.
-impl PCG32Fast {
-    const MULTIPLIER: u64 = 6364136223846793005;
-    const DEFAULT_SEED: <PCG32Fast as RandomNumberEngine>::StateType = 0xcafef00dd15ea5e5;
+impl EngineKind {
+    pub const ALL: [EngineKind; 9] = [
+        EngineKind::PCG32Fast,
+        EngineKind::PCG32FastRxsMXs,
+        EngineKind::PCG32,
+        EngineKind::PCG64,
+        EngineKind::PCG64Fast,
+        EngineKind::SplitMix64,
+        EngineKind::Xoshiro256StarStar,
+        EngineKind::Xoroshiro128Plus,
+        EngineKind::WyRand,
+    ];
 
-    /// Constructs the engine with a default or optionally given seed.
-    pub fn new(seed: Option<<PCG32Fast as RandomNumberEngine>::StateType>) -> Self {
-        Self {
-            state: Self::mangle_seed(seed),
+    pub fn name(&self) -> &'static str {
+        match self {
+            EngineKind::PCG32Fast => "pcg32-fast",
+            EngineKind::PCG32FastRxsMXs => "pcg32-fast-rxs-m-xs",
+            EngineKind::PCG32 => "pcg32",
+            EngineKind::PCG64 => "pcg64",
+            EngineKind::PCG64Fast => "pcg64-fast",
+            EngineKind::SplitMix64 => "splitmix64",
+            EngineKind::Xoshiro256StarStar => "xoshiro256starstar",
+            EngineKind::Xoroshiro128Plus => "xoroshiro128plus",
+            EngineKind::WyRand => "wyrand",
         }
     }
+}
 
-    fn mangle_seed(
-        seed: Option<<PCG32Fast as RandomNumberEngine>::StateType>,
-    ) -> <PCG32Fast as RandomNumberEngine>::StateType {
-        type StateType = <PCG32Fast as RandomNumberEngine>::StateType;
-        seed.unwrap_or(Self::DEFAULT_SEED) | (3 as StateType)
-    }
-
-    fn advance(&mut self) {
-        self.state = self.state.wrapping_mul(Self::MULTIPLIER);
-    }
-
-    // XSH RS -- high xorshift, followed by a random shift
-    #[allow(clippy::int_plus_one, clippy::bool_to_int_with_if)]
-    const fn output(
-        state: <PCG32Fast as RandomNumberEngine>::StateType,
-    ) -> <PCG32Fast as RandomNumberEngine>::ResultType {
-        type ResultType = <PCG32Fast as RandomNumberEngine>::ResultType;
-        type StateType = <PCG32Fast as RandomNumberEngine>::StateType;
-        const BITS: u8 = StateType::BITS as u8;
-        const XTYPEBITS: u8 = ResultType::BITS as u8;
-        const SPAREBITS: u8 = BITS - XTYPEBITS;
-        const OPBITS: u8 = if SPAREBITS - 5 >= 64 {
-            5
-        } else if SPAREBITS - 4 >= 32 {
-            4
-        } else if SPAREBITS - 3 >= 16 {
-            3
-        } else if SPAREBITS - 2 >= 4 {
-            2
-        } else if SPAREBITS - 1 >= 1 {
-            1
-        } else {
-            0
-        };
-        const MASK: u8 = (1 << OPBITS) - 1;
-        const MAXRANDSHIFT: u8 = MASK;
-        const TOPSPARE: u8 = OPBITS;
-        const BOTTOMSPARE: u8 = SPAREBITS - TOPSPARE;
-        const XSHIFT: u8 = TOPSPARE + (XTYPEBITS + MAXRANDSHIFT) / 2;
-        let rshift: u8 = if OPBITS != 0 {
-            (state >> (BITS - OPBITS)) as u8 & MASK
-        } else {
-            0
-        };
-        ((state ^ (state >> XSHIFT)) >> (BOTTOMSPARE - MAXRANDSHIFT + rshift)) as ResultType
+impl std::str::FromStr for EngineKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(EngineKind::name).collect();
+                format!(
+                    "unknown random number engine: {} (expected one of {})",
+                    name,
+                    names.join(", ")
+                )
+            })
     }
-
-    //fn wrapped(&self) -> bool {
-    //    self.state == 3
-    //}
 }
 
-impl std::cmp::PartialEq for PCG32Fast {
-    /// Compares the internal states of two pseudo-random number engines.
-    fn eq(&self, other: &Self) -> bool {
-        self.state == other.state
+impl std::fmt::Display for EngineKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    type ResultType = <PCG32Fast as RandomNumberEngine>::ResultType;
-
-    // The first 32 numbers of the default seeded pcg32_fast reference implementation.
-    const REF_NUMBERS: [ResultType; 32] = [
-        0xafef3262, 0x1fa2dd91, 0xea48e0b1, 0xb61b4748, 0xa52ec9aa, 0x11a1d5d3, 0x1b91f38c,
-        0xe3d4226a, 0x7061b6f3, 0x4dd9129d, 0xf7f7ab2e, 0x8b762307, 0x90622a87, 0x77f21803,
-        0x2ad14e00, 0x848768d7, 0x1ea57d20, 0xee7df193, 0x37c7776c, 0x3b2b210e, 0xaa8babda,
-        0xa08fa273, 0x71cbdd0a, 0x9493e3bb, 0xa33a0c63, 0x96fb42bf, 0x64fc0de, 0xd79cec24,
-        0xb452ead, 0x215e88ca, 0x8e41e6b2, 0x38d506a5,
-    ];
 
     #[test]
-    fn match_pcg32_fast_reference_implementation() {
-        let mut rng = PCG32Fast::new(None);
-        let mut numbers: [ResultType; 32] = [0; 32];
-        numbers.iter_mut().for_each(|x| *x = rng.get());
-        assert_eq!(numbers, REF_NUMBERS);
-    }
-
-    #[test]
-    fn random_number_engine_seed() {
-        let mut rng = PCG32Fast::new(None);
-        rng.discard(REF_NUMBERS.len() / 2);
-        rng.seed(None);
-        let mut numbers: [ResultType; 32] = [0; 32];
-        numbers.iter_mut().for_each(|x| *x = rng.get());
-        assert_eq!(numbers, REF_NUMBERS);
+    fn engine_kind_names_round_trip() {
+        for kind in EngineKind::ALL {
+            assert_eq!(kind.name().parse::<EngineKind>(), Ok(kind));
+        }
+        assert!("mt19937".parse::<EngineKind>().is_err());
     }
 
-    #[test]
-    fn random_number_engine_discard() {
-        let mut rng = PCG32Fast::new(None);
-        const MIDDLE_I: usize = REF_NUMBERS.len() / 2;
-        rng.discard(MIDDLE_I);
-        REF_NUMBERS[MIDDLE_I..]
-            .iter()
-            .for_each(|x| assert_eq!(rng.get(), *x));
+    fn check_seed_from_u64<E: SeedFromU64 + std::fmt::Debug>() {
+        assert_eq!(E::seed_from_u64(42), E::seed_from_u64(42));
+        // Seeds that only differ in the low bits must not collide, e.g. by MCGs forcing them odd.
+        for seed in 0..4 {
+            assert_ne!(E::seed_from_u64(seed), E::seed_from_u64(seed + 1));
+            assert_ne!(E::seed_from_u64(seed), E::seed_from_u64(seed ^ 1));
+        }
     }
 
     #[test]
-    fn random_number_engine_partial_eq() {
-        let mut rng1 = PCG32Fast::new(None);
-        let rng2 = PCG32Fast::new(None);
-        assert_eq!(rng1, rng2);
-        rng1.discard(1);
-        assert_ne!(rng1, rng2);
+    fn seed_from_u64() {
+        check_seed_from_u64::<PCG32Fast>();
+        check_seed_from_u64::<PCG32FastRxsMXs>();
+        check_seed_from_u64::<PCG32>();
+        check_seed_from_u64::<PCG64>();
+        check_seed_from_u64::<PCG64Fast>();
+        check_seed_from_u64::<SplitMix64>();
+        check_seed_from_u64::<Xoshiro256StarStar>();
+        check_seed_from_u64::<Xoroshiro128Plus>();
+        check_seed_from_u64::<WyRand>();
     }
 }
@@ -0,0 +1,275 @@
+use super::{EngineKind, RandomNumberEngine};
+
+/// Current version of the checkpoint formats.
+///
+/// Increment it whenever the layout of the words of an engine changes.
+pub const VERSION: u8 = 1;
+
+const MAGIC: &[u8; 3] = b"RNG";
+const TEXT_PREFIX: &str = "rng";
+
+/// Reasons why a checkpoint could not be restored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CheckpointError {
+    /// The data is truncated or not a checkpoint at all.
+    InvalidFormat,
+    /// The checkpoint was written by an incompatible version.
+    UnsupportedVersion(u8),
+    /// The checkpoint belongs to another engine.
+    EngineMismatch {
+        expected: EngineKind,
+        found: EngineKind,
+    },
+    /// The words do not describe a reachable state of the engine.
+    InvalidState,
+}
+
+impl std::fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckpointError::InvalidFormat => write!(f, "invalid random number engine checkpoint"),
+            CheckpointError::UnsupportedVersion(version) => {
+                write!(f, "unsupported checkpoint version: {}", version)
+            }
+            CheckpointError::EngineMismatch { expected, found } => {
+                write!(f, "checkpoint of {} cannot restore {}", found, expected)
+            }
+            CheckpointError::InvalidState => write!(f, "invalid random number engine state"),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+/// Engines whose complete state, including the selected stream, can be saved and restored.
+///
+/// Save-games and replays use this to checkpoint the engine mid-game and resume exactly where it
+/// left off. The state is exposed as 64 bit words, most significant first, which are wrapped in a
+/// versioned binary or text format:
+///
+/// - binary: `RNG`, version byte, engine name length byte, engine name, word count byte and the
+///   words in little endian byte order,
+/// - text: `rng<version>:<engine name>:<word>:...` with every word as 16 hexadecimal digits,
+///   e.g. `rng1:pcg32:853c49e6748fea9b:000000000000006d`.
+pub trait Checkpoint: RandomNumberEngine + Sized {
+    /// Name stored in the checkpoint to detect restoring the wrong engine.
+    const KIND: EngineKind;
+
+    /// Gets the complete state as 64 bit words.
+    fn to_words(&self) -> Vec<u64>;
+
+    /// Restores the engine from words returned by [`Checkpoint::to_words`].
+    fn from_words(words: &[u64]) -> Result<Self, CheckpointError>;
+
+    /// Serializes the engine into the binary checkpoint format.
+    fn to_bytes(&self) -> Vec<u8> {
+        let name = Self::KIND.name().as_bytes();
+        let words = self.to_words();
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 3 + name.len() + 8 * words.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(name.len() as u8);
+        bytes.extend_from_slice(name);
+        bytes.push(words.len() as u8);
+        for word in words {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Restores the engine from the binary checkpoint format.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, CheckpointError> {
+        let bytes = bytes
+            .strip_prefix(MAGIC)
+            .ok_or(CheckpointError::InvalidFormat)?;
+        let (&version, bytes) = bytes.split_first().ok_or(CheckpointError::InvalidFormat)?;
+        if version != VERSION {
+            return Err(CheckpointError::UnsupportedVersion(version));
+        }
+        let (&name_len, bytes) = bytes.split_first().ok_or(CheckpointError::InvalidFormat)?;
+        if bytes.len() < name_len as usize {
+            return Err(CheckpointError::InvalidFormat);
+        }
+        let (name, bytes) = bytes.split_at(name_len as usize);
+        check_kind::<Self>(std::str::from_utf8(name).map_err(|_| CheckpointError::InvalidFormat)?)?;
+        let (&word_count, bytes) = bytes.split_first().ok_or(CheckpointError::InvalidFormat)?;
+        if bytes.len() != 8 * word_count as usize {
+            return Err(CheckpointError::InvalidFormat);
+        }
+        let words: Vec<u64> = bytes
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        Self::from_words(&words)
+    }
+
+    /// Serializes the engine into the text checkpoint format.
+    fn to_text(&self) -> String {
+        let mut text = format!("{}{}:{}", TEXT_PREFIX, VERSION, Self::KIND.name());
+        for word in self.to_words() {
+            text.push_str(&format!(":{:016x}", word));
+        }
+        text
+    }
+
+    /// Restores the engine from the text checkpoint format.
+    fn from_text(text: &str) -> Result<Self, CheckpointError> {
+        let mut fields = text
+            .strip_prefix(TEXT_PREFIX)
+            .ok_or(CheckpointError::InvalidFormat)?
+            .split(':');
+        let version: u8 = fields
+            .next()
+            .and_then(|version| version.parse().ok())
+            .ok_or(CheckpointError::InvalidFormat)?;
+        if version != VERSION {
+            return Err(CheckpointError::UnsupportedVersion(version));
+        }
+        check_kind::<Self>(fields.next().ok_or(CheckpointError::InvalidFormat)?)?;
+        let words = fields
+            .map(|word| {
+                if word.len() != 16 || !word.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(CheckpointError::InvalidFormat);
+                }
+                u64::from_str_radix(word, 16).map_err(|_| CheckpointError::InvalidFormat)
+            })
+            .collect::<Result<Vec<u64>, _>>()?;
+        Self::from_words(&words)
+    }
+}
+
+fn check_kind<E: Checkpoint>(name: &str) -> Result<(), CheckpointError> {
+    let found: EngineKind = name.parse().map_err(|_| CheckpointError::InvalidFormat)?;
+    if found != E::KIND {
+        return Err(CheckpointError::EngineMismatch {
+            expected: E::KIND,
+            found,
+        });
+    }
+    Ok(())
+}
+
+/// Gets the exact number of words an engine expects.
+pub(crate) fn expect_words<const N: usize>(words: &[u64]) -> Result<[u64; N], CheckpointError> {
+    words.try_into().map_err(|_| CheckpointError::InvalidFormat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+
+    fn check_round_trip<E: Checkpoint + SeedFromU64 + RandomNumberEngineExt + std::fmt::Debug>() {
+        let mut rng = E::seed_from_u64(0x0123456789abcdef);
+        rng.discard(12345);
+
+        let mut restored = E::from_bytes(&rng.to_bytes()).unwrap();
+        assert_eq!(restored, rng);
+        assert!((0..100).all(|_| restored.next_u64() == rng.next_u64()));
+
+        let mut restored = E::from_text(&rng.to_text()).unwrap();
+        assert_eq!(restored, rng);
+        assert!((0..100).all(|_| restored.next_u64() == rng.next_u64()));
+
+        let mut restored = E::from_words(&rng.to_words()).unwrap();
+        assert!((0..100).all(|_| restored.next_u64() == rng.next_u64()));
+    }
+
+    #[test]
+    fn round_trip() {
+        check_round_trip::<PCG32Fast>();
+        check_round_trip::<PCG32FastRxsMXs>();
+        check_round_trip::<PCG32>();
+        check_round_trip::<PCG64>();
+        check_round_trip::<PCG64Fast>();
+        check_round_trip::<SplitMix64>();
+        check_round_trip::<Xoshiro256StarStar>();
+        check_round_trip::<Xoroshiro128Plus>();
+        check_round_trip::<WyRand>();
+    }
+
+    #[test]
+    fn stream_is_restored() {
+        let rng = PCG32::new(Some(42), Some(54));
+        let restored = PCG32::from_text(&rng.to_text()).unwrap();
+        assert_eq!(restored.stream(), 54);
+        let rng = PCG64::new(Some(42), Some(1 << 100));
+        let restored = PCG64::from_bytes(&rng.to_bytes()).unwrap();
+        assert_eq!(restored.stream(), 1 << 100);
+    }
+
+    #[test]
+    fn formats_are_stable() {
+        let rng = PCG32::from_state(0x853c49e6748fea9b, 0x6d);
+        assert_eq!(
+            rng.to_text(),
+            "rng1:pcg32:853c49e6748fea9b:000000000000006d"
+        );
+        assert_eq!(
+            rng.to_bytes(),
+            [
+                b"RNG\x01\x05pcg32\x02".as_slice(),
+                &0x853c49e6748fea9bu64.to_le_bytes(),
+                &0x6du64.to_le_bytes()
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn reject_invalid_checkpoints() {
+        let text = PCG32::new(None, None).to_text();
+        assert_eq!(
+            PCG32::from_text(&text.replacen("rng1", "rng2", 1)),
+            Err(CheckpointError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            PCG32Fast::from_text(&text),
+            Err(CheckpointError::EngineMismatch {
+                expected: EngineKind::PCG32Fast,
+                found: EngineKind::PCG32
+            })
+        );
+        assert_eq!(
+            PCG32::from_text(&text[..text.len() - 1]),
+            Err(CheckpointError::InvalidFormat)
+        );
+        assert_eq!(
+            PCG32::from_text(&text[..text.len() - 17]),
+            Err(CheckpointError::InvalidFormat)
+        );
+        assert_eq!(PCG32::from_text(""), Err(CheckpointError::InvalidFormat));
+
+        let bytes = PCG32::new(None, None).to_bytes();
+        for len in 0..bytes.len() {
+            assert!(PCG32::from_bytes(&bytes[..len]).is_err());
+        }
+        let mut future = bytes.clone();
+        future[3] = 2;
+        assert_eq!(
+            PCG32::from_bytes(&future),
+            Err(CheckpointError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn reject_unreachable_states() {
+        assert_eq!(
+            Xoshiro256StarStar::from_words(&[0; 4]),
+            Err(CheckpointError::InvalidState)
+        );
+        assert_eq!(
+            Xoroshiro128Plus::from_words(&[0; 2]),
+            Err(CheckpointError::InvalidState)
+        );
+        // The state of an MCG is always 3 modulo 4.
+        assert_eq!(
+            PCG32Fast::from_words(&[4]),
+            Err(CheckpointError::InvalidState)
+        );
+        assert_eq!(
+            PCG64Fast::from_words(&[0, 5]),
+            Err(CheckpointError::InvalidState)
+        );
+    }
+}
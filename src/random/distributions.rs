@@ -0,0 +1,468 @@
+use super::RandomNumberEngine;
+use std::ops::{Bound, RangeBounds};
+
+/// Output type of a random number engine that can be used as a source of uniform random bits.
+pub trait UniformBits: Copy {
+    fn to_u32(self) -> u32;
+    fn to_u64(self) -> u64;
+    const BITS: u32;
+}
+
+impl UniformBits for u32 {
+    fn to_u32(self) -> u32 {
+        self
+    }
+
+    fn to_u64(self) -> u64 {
+        self as u64
+    }
+
+    const BITS: u32 = u32::BITS;
+}
+
+impl UniformBits for u64 {
+    fn to_u32(self) -> u32 {
+        // The high bits have the better statistical quality for some engines.
+        (self >> 32) as u32
+    }
+
+    fn to_u64(self) -> u64 {
+        self
+    }
+
+    const BITS: u32 = u64::BITS;
+}
+
+/// Integer type which can be sampled uniformly from a range.
+pub trait UniformInt: Copy + PartialOrd {
+    /// Maps the value to an offset from `Self::MIN` in `u64` space preserving the order.
+    fn to_offset(self) -> u64;
+    /// Inverse of `to_offset`.
+    fn from_offset(offset: u64) -> Self;
+    const MIN: Self;
+    const MAX: Self;
+}
+
+macro_rules! uniform_int {
+    ($($t:ty),*) => {
+        $(
+            impl UniformInt for $t {
+                fn to_offset(self) -> u64 {
+                    (self as i128 - <$t>::MIN as i128) as u64
+                }
+
+                fn from_offset(offset: u64) -> Self {
+                    (offset as i128 + <$t>::MIN as i128) as $t
+                }
+
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+            }
+        )*
+    };
+}
+
+uniform_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// Distributions and sampling algorithms available for every random number engine.
+///
+/// All methods are unbiased: bounded integers use Lemire's multiply-and-reject method instead of
+/// the modulo operator, which would favor small values whenever the size of the range does not
+/// divide the range of the engine.
+pub trait RandomNumberEngineExt: RandomNumberEngine {
+    /// Generates 32 uniformly distributed random bits.
+    fn next_u32(&mut self) -> u32;
+
+    /// Generates 64 uniformly distributed random bits.
+    fn next_u64(&mut self) -> u64;
+
+    /// Generates a uniformly distributed integer in `0..n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    fn gen_below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "cannot sample from an empty range");
+        if n <= u32::MAX as u64 {
+            let n = n as u32;
+            let mut m = self.next_u32() as u64 * n as u64;
+            if (m as u32) < n {
+                let threshold = n.wrapping_neg() % n;
+                while (m as u32) < threshold {
+                    m = self.next_u32() as u64 * n as u64;
+                }
+            }
+            m >> 32
+        } else {
+            let mut m = self.next_u64() as u128 * n as u128;
+            if (m as u64) < n {
+                let threshold = n.wrapping_neg() % n;
+                while (m as u64) < threshold {
+                    m = self.next_u64() as u128 * n as u128;
+                }
+            }
+            (m >> 64) as u64
+        }
+    }
+
+    /// Generates a uniformly distributed integer in the given range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is empty.
+    fn gen_range<T: UniformInt>(&mut self, range: impl RangeBounds<T>) -> T {
+        let low = match range.start_bound() {
+            Bound::Included(low) => low.to_offset(),
+            Bound::Excluded(low) => low
+                .to_offset()
+                .checked_add(1)
+                .expect("cannot sample from an empty range"),
+            Bound::Unbounded => T::MIN.to_offset(),
+        };
+        let high = match range.end_bound() {
+            Bound::Included(high) => high.to_offset(),
+            Bound::Excluded(high) => high
+                .to_offset()
+                .checked_sub(1)
+                .expect("cannot sample from an empty range"),
+            Bound::Unbounded => T::MAX.to_offset(),
+        };
+        assert!(low <= high, "cannot sample from an empty range");
+        let offset = match (high - low).checked_add(1) {
+            Some(n) => self.gen_below(n),
+            // The range covers all 64 bit values.
+            None => self.next_u64(),
+        };
+        T::from_offset(low + offset)
+    }
+
+    /// Generates a uniformly distributed `f64` in `[0, 1)`.
+    fn gen_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Generates a uniformly distributed `f32` in `[0, 1)`.
+    fn gen_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+
+    /// Returns `true` with probability `p`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is not in `[0, 1]`.
+    fn gen_bool(&mut self, p: f64) -> bool {
+        assert!((0.0..=1.0).contains(&p), "probability {} not in [0, 1]", p);
+        // Compare in integer space so `p == 1.0` is always true and `p == 0.0` is always false.
+        const SCALE: f64 = (1u64 << 53) as f64;
+        ((self.next_u64() >> 11) as f64) < p * SCALE
+    }
+
+    /// Chooses a uniformly distributed element of a slice.
+    fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        match items.len() {
+            0 => None,
+            len => Some(&items[self.gen_range(0..len)]),
+        }
+    }
+
+    /// Chooses an element of a slice with a probability proportional to its weight.
+    ///
+    /// Returns `None` if the slice is empty, a weight is negative or not finite, or all weights
+    /// are zero.
+    fn choose_weighted<'a, T>(
+        &mut self,
+        items: &'a [T],
+        weight: impl Fn(&T) -> f64,
+    ) -> Option<&'a T> {
+        let mut total = 0.0;
+        for item in items {
+            let weight = weight(item);
+            if !(weight.is_finite() && weight >= 0.0) {
+                return None;
+            }
+            total += weight;
+        }
+        if !(total.is_finite() && total > 0.0) {
+            return None;
+        }
+        let mut target = self.gen_f64() * total;
+        let mut last = None;
+        for item in items {
+            let weight = weight(item);
+            if weight > 0.0 {
+                if target < weight {
+                    return Some(item);
+                }
+                target -= weight;
+                last = Some(item);
+            }
+        }
+        // Rounding errors may leave a tiny remainder after the last item.
+        last
+    }
+
+    /// Shuffles a slice in place using the Fisher-Yates algorithm.
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.gen_range(0..=i));
+        }
+    }
+
+    /// Chooses `amount` distinct indices in `0..len` in random order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `amount` is greater than `len`.
+    fn sample_indices(&mut self, len: usize, amount: usize) -> Vec<usize> {
+        assert!(amount <= len, "cannot sample {} of {} items", amount, len);
+        // Partial Fisher-Yates shuffle.
+        let mut indices: Vec<usize> = (0..len).collect();
+        for i in 0..amount {
+            indices.swap(i, self.gen_range(i..len));
+        }
+        indices.truncate(amount);
+        indices
+    }
+
+    /// Chooses `amount` distinct elements of a slice in random order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `amount` is greater than the length of the slice.
+    fn sample<'a, T>(&mut self, items: &'a [T], amount: usize) -> Vec<&'a T> {
+        self.sample_indices(items.len(), amount)
+            .into_iter()
+            .map(|i| &items[i])
+            .collect()
+    }
+}
+
+impl<E> RandomNumberEngineExt for E
+where
+    E: RandomNumberEngine,
+    E::ResultType: UniformBits,
+{
+    fn next_u32(&mut self) -> u32 {
+        self.get().to_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        if E::ResultType::BITS >= 64 {
+            self.get().to_u64()
+        } else {
+            let high = self.get().to_u64();
+            let low = self.get().to_u64();
+            (high << 32) | low
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{PCG32Fast, PCG64};
+
+    /// Critical values of the chi-squared distribution for a significance level of 0.001.
+    fn chi_squared_critical_value(degrees_of_freedom: usize) -> f64 {
+        match degrees_of_freedom {
+            1 => 10.828,
+            2 => 13.816,
+            3 => 16.266,
+            5 => 20.515,
+            9 => 27.877,
+            15 => 37.697,
+            _ => panic!(
+                "no critical value for {} degrees of freedom",
+                degrees_of_freedom
+            ),
+        }
+    }
+
+    /// Asserts that the observed counts fit the expected probabilities.
+    fn assert_chi_squared(observed: &[usize], expected: &[f64]) {
+        let total: usize = observed.iter().sum();
+        let statistic: f64 = observed
+            .iter()
+            .zip(expected)
+            .map(|(observed, p)| {
+                let expected = p * total as f64;
+                (*observed as f64 - expected).powi(2) / expected
+            })
+            .sum();
+        let critical_value = chi_squared_critical_value(observed.len() - 1);
+        assert!(
+            statistic < critical_value,
+            "chi-squared statistic {} exceeds {} for {:?}",
+            statistic,
+            critical_value,
+            observed
+        );
+    }
+
+    fn uniform(n: usize) -> Vec<f64> {
+        vec![1.0 / n as f64; n]
+    }
+
+    const SAMPLES: usize = 100_000;
+
+    #[test]
+    fn gen_range_bounds() {
+        let mut rng = PCG32Fast::new(None);
+        for _ in 0..1000 {
+            assert!((3..7).contains(&rng.gen_range(3..7)));
+            assert!((-5..=5).contains(&rng.gen_range(-5..=5)));
+            assert_eq!(rng.gen_range(9u8..10), 9);
+            assert_eq!(rng.gen_range(i64::MAX..), i64::MAX);
+        }
+        rng.gen_range::<u64>(..);
+        rng.gen_range::<i8>(..);
+    }
+
+    #[test]
+    #[should_panic]
+    fn gen_range_empty() {
+        PCG32Fast::new(None).gen_range(5..5);
+    }
+
+    #[test]
+    fn gen_range_is_uniform() {
+        let mut rng = PCG32Fast::new(None);
+        let mut counts = [0; 10];
+        for _ in 0..SAMPLES {
+            counts[rng.gen_range(0..10)] += 1;
+        }
+        assert_chi_squared(&counts, &uniform(10));
+
+        let mut rng = PCG64::new(None, None);
+        let mut counts = [0; 10];
+        for _ in 0..SAMPLES {
+            counts[(rng.gen_range(-50i64..50) + 50) as usize / 10] += 1;
+        }
+        assert_chi_squared(&counts, &uniform(10));
+    }
+
+    #[test]
+    fn gen_range_is_unbiased_for_large_ranges() {
+        // Taking a 32 bit number modulo 3 * 2^30 would hit the lower third of this range twice
+        // as often as the other thirds.
+        const N: u64 = 3 << 30;
+        let mut rng = PCG32Fast::new(None);
+        let mut counts = [0; 3];
+        for _ in 0..SAMPLES {
+            counts[(rng.gen_below(N) / (1 << 30)) as usize] += 1;
+        }
+        assert_chi_squared(&counts, &uniform(3));
+
+        // Same for 64 bit ranges.
+        const M: u64 = 3 << 62;
+        let mut counts = [0; 3];
+        for _ in 0..SAMPLES {
+            counts[(rng.gen_below(M) / (1 << 62)) as usize] += 1;
+        }
+        assert_chi_squared(&counts, &uniform(3));
+    }
+
+    #[test]
+    fn gen_float_is_uniform() {
+        let mut rng = PCG32Fast::new(None);
+        let mut counts_f64 = [0; 10];
+        let mut counts_f32 = [0; 10];
+        for _ in 0..SAMPLES {
+            let x = rng.gen_f64();
+            assert!((0.0..1.0).contains(&x));
+            counts_f64[(x * 10.0) as usize] += 1;
+            let y = rng.gen_f32();
+            assert!((0.0..1.0).contains(&y));
+            counts_f32[(y * 10.0) as usize] += 1;
+        }
+        assert_chi_squared(&counts_f64, &uniform(10));
+        assert_chi_squared(&counts_f32, &uniform(10));
+    }
+
+    #[test]
+    fn gen_bool() {
+        let mut rng = PCG32Fast::new(None);
+        assert!((0..1000).all(|_| rng.gen_bool(1.0)));
+        assert!((0..1000).all(|_| !rng.gen_bool(0.0)));
+        let hits = (0..SAMPLES).filter(|_| rng.gen_bool(0.3)).count();
+        assert_chi_squared(&[hits, SAMPLES - hits], &[0.3, 0.7]);
+    }
+
+    #[test]
+    fn choose() {
+        let mut rng = PCG32Fast::new(None);
+        assert_eq!(rng.choose::<u8>(&[]), None);
+        let items = [0, 1, 2, 3, 4, 5];
+        let mut counts = [0; 6];
+        for _ in 0..SAMPLES {
+            counts[*rng.choose(&items).unwrap()] += 1;
+        }
+        assert_chi_squared(&counts, &uniform(6));
+    }
+
+    #[test]
+    fn choose_weighted() {
+        let mut rng = PCG32Fast::new(None);
+        let items = [(0, 1.0), (1, 0.0), (2, 2.0), (3, 3.0)];
+        let mut counts = [0; 4];
+        for _ in 0..SAMPLES {
+            counts[rng.choose_weighted(&items, |x| x.1).unwrap().0] += 1;
+        }
+        assert_eq!(counts[1], 0);
+        assert_chi_squared(
+            &[counts[0], counts[2], counts[3]],
+            &[1.0 / 6.0, 2.0 / 6.0, 3.0 / 6.0],
+        );
+
+        assert!(rng.choose_weighted(&items, |_| 0.0).is_none());
+        assert!(rng.choose_weighted(&items, |_| -1.0).is_none());
+        assert!(rng.choose_weighted(&items, |_| f64::NAN).is_none());
+        assert!(rng.choose_weighted(&items[..0], |x| x.1).is_none());
+    }
+
+    #[test]
+    fn shuffle_is_uniform() {
+        let mut rng = PCG32Fast::new(None);
+        // All 24 permutations of 4 elements, indexed by their Lehmer code.
+        let mut counts = [0; 24];
+        for _ in 0..SAMPLES {
+            let mut items = [0, 1, 2, 3];
+            rng.shuffle(&mut items);
+            let mut index = 0;
+            for i in 0..items.len() {
+                let smaller = items[i + 1..].iter().filter(|x| **x < items[i]).count();
+                index = index * (items.len() - i) + smaller;
+            }
+            counts[index] += 1;
+        }
+        // Use bins of permutations with the same first element to keep the test simple.
+        let bins: Vec<usize> = counts.chunks(6).map(|x| x.iter().sum()).collect();
+        assert_chi_squared(&bins, &uniform(4));
+        assert!(counts.iter().all(|x| *x > 0));
+    }
+
+    #[test]
+    fn sample() {
+        let mut rng = PCG32Fast::new(None);
+        let items: Vec<usize> = (0..16).collect();
+        let mut counts = [0; 16];
+        for _ in 0..SAMPLES / 4 {
+            let sample = rng.sample(&items, 4);
+            assert_eq!(sample.len(), 4);
+            for (i, x) in sample.iter().enumerate() {
+                assert!(!sample[i + 1..].contains(x));
+                counts[**x] += 1;
+            }
+        }
+        assert_chi_squared(&counts, &uniform(16));
+        assert_eq!(rng.sample_indices(5, 5).len(), 5);
+        assert!(rng.sample_indices(5, 0).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn sample_too_many() {
+        PCG32Fast::new(None).sample_indices(3, 4);
+    }
+}
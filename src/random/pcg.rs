@@ -0,0 +1,729 @@
+use super::checkpoint::{Checkpoint, CheckpointError};
+use super::{EngineKind, RandomNumberEngine, SeedFromU64, SplitMix64, DEFAULT_SEED};
+use std::marker::PhantomData;
+
+/// Output function of a PCG engine which permutes the internal state into the generated value.
+pub trait OutputFunction<S, R> {
+    fn output(state: S) -> R;
+}
+
+/// XSH RS -- high xorshift, followed by a random shift.
+#[derive(Debug)]
+pub struct XshRs;
+
+/// XSH RR -- high xorshift, followed by a random rotate.
+#[derive(Debug)]
+pub struct XshRr;
+
+/// RXS M XS -- random xorshift, mcg multiply, fixed xorshift.
+///
+/// Statistically the strongest output function, but also the slowest one.
+#[derive(Debug)]
+pub struct RxsMXs;
+
+/// XSL RR -- fixed xorshift to low bits, random rotate.
+#[derive(Debug)]
+pub struct XslRr;
+
+impl OutputFunction<u64, u32> for XshRs {
+    #[allow(clippy::int_plus_one, clippy::bool_to_int_with_if)]
+    fn output(state: u64) -> u32 {
+        const BITS: u8 = u64::BITS as u8;
+        const XTYPEBITS: u8 = u32::BITS as u8;
+        const SPAREBITS: u8 = BITS - XTYPEBITS;
+        const OPBITS: u8 = if SPAREBITS - 5 >= 64 {
+            5
+        } else if SPAREBITS - 4 >= 32 {
+            4
+        } else if SPAREBITS - 3 >= 16 {
+            3
+        } else if SPAREBITS - 2 >= 4 {
+            2
+        } else if SPAREBITS - 1 >= 1 {
+            1
+        } else {
+            0
+        };
+        const MASK: u8 = (1 << OPBITS) - 1;
+        const MAXRANDSHIFT: u8 = MASK;
+        const TOPSPARE: u8 = OPBITS;
+        const BOTTOMSPARE: u8 = SPAREBITS - TOPSPARE;
+        const XSHIFT: u8 = TOPSPARE + (XTYPEBITS + MAXRANDSHIFT) / 2;
+        let rshift: u8 = if OPBITS != 0 {
+            (state >> (BITS - OPBITS)) as u8 & MASK
+        } else {
+            0
+        };
+        ((state ^ (state >> XSHIFT)) >> (BOTTOMSPARE - MAXRANDSHIFT + rshift)) as u32
+    }
+}
+
+impl OutputFunction<u64, u32> for XshRr {
+    fn output(state: u64) -> u32 {
+        let rotate = (state >> 59) as u32;
+        (((state ^ (state >> 18)) >> 27) as u32).rotate_right(rotate)
+    }
+}
+
+impl OutputFunction<u64, u32> for RxsMXs {
+    fn output(state: u64) -> u32 {
+        const MCG_MULTIPLIER: u64 = 12605985483714917081;
+        let random_shift = (state >> 60) as u32;
+        let state = (state ^ (state >> (4 + random_shift))).wrapping_mul(MCG_MULTIPLIER);
+        let result = (state >> 32) as u32;
+        result ^ (result >> 22)
+    }
+}
+
+impl OutputFunction<u128, u64> for XslRr {
+    fn output(state: u128) -> u64 {
+        let rotate = (state >> 122) as u32;
+        ((state >> 64) as u64 ^ state as u64).rotate_right(rotate)
+    }
+}
+
+/// Advances an LCG state by `delta` steps in O(log delta).
+///
+/// Computes `state * mult^delta + plus * (mult^delta - 1) / (mult - 1)` by repeated squaring as
+/// described in F. Brown, "Random Number Generation with Arbitrary Stride". The period of the
+/// generator divides 2^BITS, so jumping back by `n` steps is the same as jumping ahead by
+/// `2^BITS - n` steps.
+macro_rules! lcg_advance {
+    ($name:ident, $state:ty) => {
+        const fn $name(
+            state: $state,
+            mut delta: $state,
+            mut mult: $state,
+            mut plus: $state,
+        ) -> $state {
+            let mut acc_mult: $state = 1;
+            let mut acc_plus: $state = 0;
+            while delta > 0 {
+                if delta & 1 != 0 {
+                    acc_mult = acc_mult.wrapping_mul(mult);
+                    acc_plus = acc_plus.wrapping_mul(mult).wrapping_add(plus);
+                }
+                plus = mult.wrapping_add(1).wrapping_mul(plus);
+                mult = mult.wrapping_mul(mult);
+                delta >>= 1;
+            }
+            acc_mult.wrapping_mul(state).wrapping_add(acc_plus)
+        }
+    };
+}
+
+lcg_advance!(lcg_advance_u64, u64);
+lcg_advance!(lcg_advance_u128, u128);
+
+/// Expands a 64 bit seed into the state of a PCG engine.
+///
+/// Truncating keeps the low half for 64 bit states.
+fn expand_seed(seed: u64) -> u128 {
+    let mut splitmix = SplitMix64::new(Some(seed));
+    let high = splitmix.next();
+    let low = splitmix.next();
+    ((high as u128) << 64) | low as u128
+}
+
+/// Splits a state into 64 bit words, most significant first.
+fn split_words(value: u128, bits: u32) -> impl Iterator<Item = u64> {
+    (0..bits / 64)
+        .rev()
+        .map(move |i| (value >> (64 * i)) as u64)
+}
+
+/// Joins 64 bit words, most significant first, into a state.
+fn join_words(words: &[u64]) -> u128 {
+    words
+        .iter()
+        .fold(0, |value, word| (value << 64) | *word as u128)
+}
+
+/// Defines a PCG engine based on a multiplicative congruential generator.
+///
+/// MCGs have no increment, so there is no stream selection and the period is 2^(BITS-2).
+/// They are slightly faster than their LCG counterparts.
+///
+/// Like the reference implementation, engines with up to 64 bit state output the state before
+/// advancing it, larger engines output the advanced state.
+macro_rules! mcg_engine {
+    ($(#[$meta:meta])* $name:ident, $state:ty, $result:ty, $multiplier:expr, $advance:ident, $output_previous:expr) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        pub struct $name<O> {
+            state: $state,
+            output: PhantomData<O>,
+        }
+
+        impl<O: OutputFunction<$state, $result>> RandomNumberEngine for $name<O> {
+            type ResultType = $result;
+            type StateType = $state;
+
+            const MIN: Self::ResultType = Self::ResultType::MIN;
+            const MAX: Self::ResultType = Self::ResultType::MAX;
+
+            fn seed(&mut self, seed: Option<Self::StateType>) {
+                self.state = Self::mangle_seed(seed);
+            }
+
+            fn state(&self) -> Self::StateType {
+                self.state
+            }
+
+            fn get(&mut self) -> Self::ResultType {
+                if $output_previous {
+                    let old_state = self.state;
+                    self.advance();
+                    O::output(old_state)
+                } else {
+                    self.advance();
+                    O::output(self.state)
+                }
+            }
+
+            fn discard(&mut self, z: usize) {
+                self.advance_by(z as i128);
+            }
+        }
+
+        impl<O: OutputFunction<$state, $result>> $name<O> {
+            const MULTIPLIER: $state = $multiplier;
+
+            /// Constructs the engine with a default or optionally given seed.
+            pub fn new(seed: Option<$state>) -> Self {
+                Self {
+                    state: Self::mangle_seed(seed),
+                    output: PhantomData,
+                }
+            }
+
+            /// Restores an engine from a value returned by `state()`.
+            ///
+            /// Returns `None` if the state cannot be reached, i.e. it is not 3 modulo 4.
+            pub fn from_state(state: $state) -> Option<Self> {
+                (state & 3 == 3).then_some(Self {
+                    state,
+                    output: PhantomData,
+                })
+            }
+
+            fn mangle_seed(seed: Option<$state>) -> $state {
+                seed.unwrap_or(DEFAULT_SEED as $state) | 3
+            }
+
+            fn advance(&mut self) {
+                self.state = self.state.wrapping_mul(Self::MULTIPLIER);
+            }
+
+            /// Advances the engine's state by `delta` steps in O(log delta).
+            ///
+            /// Negative values move the engine back, so `advance_by(-1)` followed by `get()`
+            /// returns the previously generated value again.
+            pub fn advance_by(&mut self, delta: i128) {
+                // Truncating reduces `delta` modulo 2^BITS which is a multiple of the period.
+                self.state = $advance(self.state, delta as $state, Self::MULTIPLIER, 0);
+            }
+        }
+
+        impl<O> std::cmp::PartialEq for $name<O> {
+            /// Compares the internal states of two pseudo-random number engines.
+            fn eq(&self, other: &Self) -> bool {
+                self.state == other.state
+            }
+        }
+
+        impl<O: OutputFunction<$state, $result>> SeedFromU64 for $name<O> {
+            fn seed_from_u64(seed: u64) -> Self {
+                Self::new(Some(expand_seed(seed) as $state))
+            }
+        }
+    };
+}
+
+/// Defines a PCG engine based on a linear congruential generator with selectable stream.
+///
+/// The stream is selected by the odd increment of the LCG. Engines on different streams produce
+/// independent sequences even when they are seeded with the same value.
+macro_rules! lcg_engine {
+    ($(#[$meta:meta])* $name:ident, $state:ty, $result:ty, $multiplier:expr, $increment:expr, $advance:ident, $output_previous:expr) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        pub struct $name<O> {
+            state: $state,
+            increment: $state,
+            output: PhantomData<O>,
+        }
+
+        impl<O: OutputFunction<$state, $result>> RandomNumberEngine for $name<O> {
+            type ResultType = $result;
+            type StateType = $state;
+
+            const MIN: Self::ResultType = Self::ResultType::MIN;
+            const MAX: Self::ResultType = Self::ResultType::MAX;
+
+            /// Sets the current state of the engine, keeping the selected stream.
+            fn seed(&mut self, seed: Option<Self::StateType>) {
+                self.state = Self::mangle_seed(seed, self.increment);
+            }
+
+            fn state(&self) -> Self::StateType {
+                self.state
+            }
+
+            fn get(&mut self) -> Self::ResultType {
+                if $output_previous {
+                    let old_state = self.state;
+                    self.advance();
+                    O::output(old_state)
+                } else {
+                    self.advance();
+                    O::output(self.state)
+                }
+            }
+
+            fn discard(&mut self, z: usize) {
+                self.advance_by(z as i128);
+            }
+        }
+
+        impl<O: OutputFunction<$state, $result>> $name<O> {
+            const MULTIPLIER: $state = $multiplier;
+            const DEFAULT_INCREMENT: $state = $increment;
+
+            /// Constructs the engine with a default or optionally given seed and stream.
+            pub fn new(seed: Option<$state>, stream: Option<$state>) -> Self {
+                let increment = stream.map_or(Self::DEFAULT_INCREMENT, Self::stream_increment);
+                Self {
+                    state: Self::mangle_seed(seed, increment),
+                    increment,
+                    output: PhantomData,
+                }
+            }
+
+            /// Restores an engine from values returned by `state()` and `stream()`.
+            pub fn from_state(state: $state, stream: $state) -> Self {
+                Self {
+                    state,
+                    increment: Self::stream_increment(stream),
+                    output: PhantomData,
+                }
+            }
+
+            fn stream_increment(stream: $state) -> $state {
+                (stream << 1) | 1
+            }
+
+            fn mangle_seed(seed: Option<$state>, increment: $state) -> $state {
+                seed.unwrap_or(DEFAULT_SEED as $state)
+                    .wrapping_add(increment)
+                    .wrapping_mul(Self::MULTIPLIER)
+                    .wrapping_add(increment)
+            }
+
+            /// Gets the selected stream.
+            pub fn stream(&self) -> $state {
+                self.increment >> 1
+            }
+
+            /// Selects another stream without changing the state.
+            ///
+            /// The most significant bit of `stream` is ignored.
+            pub fn set_stream(&mut self, stream: $state) {
+                self.increment = Self::stream_increment(stream);
+            }
+
+            fn advance(&mut self) {
+                self.state = self
+                    .state
+                    .wrapping_mul(Self::MULTIPLIER)
+                    .wrapping_add(self.increment);
+            }
+
+            /// Advances the engine's state by `delta` steps in O(log delta).
+            ///
+            /// Negative values move the engine back, so `advance_by(-1)` followed by `get()`
+            /// returns the previously generated value again.
+            pub fn advance_by(&mut self, delta: i128) {
+                // Truncating reduces `delta` modulo 2^BITS which is a multiple of the period.
+                self.state = $advance(
+                    self.state,
+                    delta as $state,
+                    Self::MULTIPLIER,
+                    self.increment,
+                );
+            }
+        }
+
+        impl<O> std::cmp::PartialEq for $name<O> {
+            /// Compares the internal states and streams of two pseudo-random number engines.
+            fn eq(&self, other: &Self) -> bool {
+                self.state == other.state && self.increment == other.increment
+            }
+        }
+
+        impl<O: OutputFunction<$state, $result>> SeedFromU64 for $name<O> {
+            fn seed_from_u64(seed: u64) -> Self {
+                Self::new(Some(expand_seed(seed) as $state), None)
+            }
+        }
+    };
+}
+
+/// Implements [`Checkpoint`] for an MCG based engine, storing only the state.
+macro_rules! mcg_checkpoint {
+    ($name:ident, $state:ty) => {
+        impl Checkpoint for $name {
+            const KIND: EngineKind = EngineKind::$name;
+
+            fn to_words(&self) -> Vec<u64> {
+                split_words(self.state as u128, <$state>::BITS).collect()
+            }
+
+            fn from_words(words: &[u64]) -> Result<Self, CheckpointError> {
+                if words.len() as u32 != <$state>::BITS / 64 {
+                    return Err(CheckpointError::InvalidFormat);
+                }
+                Self::from_state(join_words(words) as $state).ok_or(CheckpointError::InvalidState)
+            }
+        }
+    };
+}
+
+/// Implements [`Checkpoint`] for an LCG based engine, storing the state followed by the stream.
+macro_rules! lcg_checkpoint {
+    ($name:ident, $state:ty) => {
+        impl Checkpoint for $name {
+            const KIND: EngineKind = EngineKind::$name;
+
+            fn to_words(&self) -> Vec<u64> {
+                split_words(self.state as u128, <$state>::BITS)
+                    .chain(split_words(self.stream() as u128, <$state>::BITS))
+                    .collect()
+            }
+
+            fn from_words(words: &[u64]) -> Result<Self, CheckpointError> {
+                let len = (<$state>::BITS / 64) as usize;
+                if words.len() != 2 * len {
+                    return Err(CheckpointError::InvalidFormat);
+                }
+                let (state, stream) = words.split_at(len);
+                Ok(Self::from_state(
+                    join_words(state) as $state,
+                    join_words(stream) as $state,
+                ))
+            }
+        }
+    };
+}
+
+mcg_engine!(
+    /// PCG engine with a 64 bit MCG state and 32 bit output.
+    Mcg64,
+    u64,
+    u32,
+    6364136223846793005,
+    lcg_advance_u64,
+    true
+);
+
+mcg_engine!(
+    /// PCG engine with a 128 bit MCG state and 64 bit output.
+    Mcg128,
+    u128,
+    u64,
+    0x2360ed051fc65da44385df649fccf645,
+    lcg_advance_u128,
+    false
+);
+
+lcg_engine!(
+    /// PCG engine with a 64 bit LCG state, selectable stream and 32 bit output.
+    Lcg64,
+    u64,
+    u32,
+    6364136223846793005,
+    1442695040888963407,
+    lcg_advance_u64,
+    true
+);
+
+lcg_engine!(
+    /// PCG engine with a 128 bit LCG state, selectable stream and 64 bit output.
+    Lcg128,
+    u128,
+    u64,
+    0x2360ed051fc65da44385df649fccf645,
+    0x5851f42d4c957f2d14057b7ef767814f,
+    lcg_advance_u128,
+    false
+);
+
+/// Random number engine for 32 bit random numbers.
+///
+/// This is an implementation of the pcg32_fast PRNG from <https://www.pcg-random.org>.
+/// The internal state has 64 bit, so there are no 128 bit operations required.
+pub type PCG32Fast = Mcg64<XshRs>;
+
+/// Random number engine for 32 bit random numbers using the RXS M XS output function.
+///
+/// Same as [`PCG32Fast`] but with better statistical quality at the cost of speed.
+pub type PCG32FastRxsMXs = Mcg64<RxsMXs>;
+
+/// Random number engine for 32 bit random numbers with selectable stream.
+///
+/// This is an implementation of the pcg32 PRNG from <https://www.pcg-random.org>.
+pub type PCG32 = Lcg64<XshRr>;
+
+/// Random number engine for 64 bit random numbers with selectable stream.
+///
+/// This is an implementation of the pcg64 PRNG from <https://www.pcg-random.org>.
+/// The internal state has 128 bit.
+pub type PCG64 = Lcg128<XslRr>;
+
+/// Random number engine for 64 bit random numbers.
+///
+/// This is an implementation of the pcg64_fast PRNG from <https://www.pcg-random.org>.
+/// The internal state has 128 bit.
+pub type PCG64Fast = Mcg128<XslRr>;
+
+mcg_checkpoint!(PCG32Fast, u64);
+mcg_checkpoint!(PCG32FastRxsMXs, u64);
+lcg_checkpoint!(PCG32, u64);
+lcg_checkpoint!(PCG64, u128);
+mcg_checkpoint!(PCG64Fast, u128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    type ResultType = <PCG32Fast as RandomNumberEngine>::ResultType;
+
+    // The first 32 numbers of the default seeded pcg32_fast reference implementation.
+    const REF_NUMBERS: [ResultType; 32] = [
+        0xafef3262, 0x1fa2dd91, 0xea48e0b1, 0xb61b4748, 0xa52ec9aa, 0x11a1d5d3, 0x1b91f38c,
+        0xe3d4226a, 0x7061b6f3, 0x4dd9129d, 0xf7f7ab2e, 0x8b762307, 0x90622a87, 0x77f21803,
+        0x2ad14e00, 0x848768d7, 0x1ea57d20, 0xee7df193, 0x37c7776c, 0x3b2b210e, 0xaa8babda,
+        0xa08fa273, 0x71cbdd0a, 0x9493e3bb, 0xa33a0c63, 0x96fb42bf, 0x64fc0de, 0xd79cec24,
+        0xb452ead, 0x215e88ca, 0x8e41e6b2, 0x38d506a5,
+    ];
+
+    fn generate<T: RandomNumberEngine, const N: usize>(rng: &mut T) -> [T::ResultType; N]
+    where
+        T::ResultType: Default + Copy,
+    {
+        let mut numbers = [T::ResultType::default(); N];
+        numbers.iter_mut().for_each(|x| *x = rng.get());
+        numbers
+    }
+
+    #[test]
+    fn match_pcg32_fast_reference_implementation() {
+        let mut rng = PCG32Fast::new(None);
+        let mut numbers: [ResultType; 32] = [0; 32];
+        numbers.iter_mut().for_each(|x| *x = rng.get());
+        assert_eq!(numbers, REF_NUMBERS);
+    }
+
+    #[test]
+    fn match_pcg32_reference_implementation() {
+        // pcg32_srandom_r(&rng, 42, 54) from the pcg32 demo.
+        let mut rng = PCG32::new(Some(42), Some(54));
+        assert_eq!(
+            generate(&mut rng),
+            [0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]
+        );
+        // Default constructed pcg32.
+        let mut rng = PCG32::new(None, None);
+        assert_eq!(
+            generate(&mut rng),
+            [0x285594ea, 0x190ca349, 0xcbc42ff2, 0xd6508153, 0xc2a8052f, 0x0f55ac5f]
+        );
+    }
+
+    #[test]
+    fn match_pcg64_reference_implementation() {
+        // pcg64_srandom_r(&rng, 42, 54) from the pcg64 demo.
+        let mut rng = PCG64::new(Some(42), Some(54));
+        assert_eq!(
+            generate(&mut rng),
+            [
+                0x86b1da1d72062b68,
+                0x1304aa46c9853d39,
+                0xa3670e9e0dd50358,
+                0xf9090e529a7dae00,
+                0xc85b9fd837996f2c,
+                0x606121f8e3919196
+            ]
+        );
+        // Default constructed pcg64.
+        let mut rng = PCG64::new(None, None);
+        assert_eq!(
+            generate(&mut rng),
+            [
+                0xcf7dbe684e0c4045,
+                0x15642875dfe1e67c,
+                0x32f049df2f50d811,
+                0x98c1d0a163e1f856,
+                0x0743e58f0360d766,
+                0xfee9e4fc03479bf6
+            ]
+        );
+    }
+
+    #[test]
+    fn match_pcg64_fast_reference_implementation() {
+        // pcg64_fast seeded with 42. The expected output of the official PCG test suite (C
+        // version), as copied into the Mcg128Xsl64 reference test of the rand_pcg crate.
+        let mut rng = PCG64Fast::new(Some(42));
+        assert_eq!(
+            generate(&mut rng),
+            [
+                0x63b4a3a813ce700a,
+                0x382954200617ab24,
+                0xa7fd85ae3fe950ce,
+                0xd715286aa2887737,
+                0x60c92fee2e59f32c,
+                0x84c4e96beff30017
+            ]
+        );
+    }
+
+    #[test]
+    fn match_mcg_rxs_m_xs_64_32_reference_implementation() {
+        // Computed with a port of the engines and output mixins of the PCG C++ library
+        // (pcg_random.hpp), not taken from a published test suite.
+        let mut rng = PCG32FastRxsMXs::new(None);
+        assert_eq!(
+            generate(&mut rng),
+            [0x5153f6e5, 0xdd16d149, 0x09c24cbc, 0x9f1cba0d, 0xe6cb1cf3, 0x0954af25]
+        );
+        let mut rng = PCG32FastRxsMXs::new(Some(42));
+        assert_eq!(
+            generate(&mut rng),
+            [0x04abbd46, 0xcf870843, 0x6ac576b8, 0x0eba0e55, 0x7b28878f, 0xe9d3b5e3]
+        );
+    }
+
+    #[test]
+    fn random_number_engine_seed() {
+        let mut rng = PCG32Fast::new(None);
+        rng.discard(REF_NUMBERS.len() / 2);
+        rng.seed(None);
+        let mut numbers: [ResultType; 32] = [0; 32];
+        numbers.iter_mut().for_each(|x| *x = rng.get());
+        assert_eq!(numbers, REF_NUMBERS);
+    }
+
+    #[test]
+    fn random_number_engine_seed_keeps_stream() {
+        let mut rng = PCG32::new(Some(1), Some(54));
+        rng.seed(Some(42));
+        assert_eq!(rng, PCG32::new(Some(42), Some(54)));
+    }
+
+    #[test]
+    fn random_number_engine_discard() {
+        let mut rng = PCG32Fast::new(None);
+        const MIDDLE_I: usize = REF_NUMBERS.len() / 2;
+        rng.discard(MIDDLE_I);
+        REF_NUMBERS[MIDDLE_I..]
+            .iter()
+            .for_each(|x| assert_eq!(rng.get(), *x));
+    }
+
+    #[test]
+    fn streams() {
+        let mut rng1 = PCG32::new(Some(42), Some(1));
+        let mut rng2 = PCG32::new(Some(42), Some(2));
+        assert_eq!(rng1.stream(), 1);
+        assert_eq!(rng2.stream(), 2);
+        assert_ne!(rng1, rng2);
+        let numbers1: [u32; 16] = generate(&mut rng1);
+        let numbers2: [u32; 16] = generate(&mut rng2);
+        assert!(numbers1.iter().zip(numbers2.iter()).all(|(a, b)| a != b));
+
+        let mut rng3 = PCG64::new(Some(42), Some(1));
+        rng3.set_stream(2);
+        assert_eq!(rng3.stream(), 2);
+        assert_ne!(rng3, PCG64::new(Some(42), Some(1)));
+    }
+
+    #[test]
+    fn advance_by_matches_stepwise_advancement() {
+        let mut rng1 = PCG32Fast::new(None);
+        let mut rng2 = PCG32Fast::new(None);
+        let mut rng3 = PCG32::new(None, Some(54));
+        let mut rng4 = PCG32::new(None, Some(54));
+        let mut rng5 = PCG64::new(None, Some(54));
+        let mut rng6 = PCG64::new(None, Some(54));
+        for delta in [0, 1, 2, 3, 17, 1000, 123_456] {
+            rng1.advance_by(delta);
+            rng3.advance_by(delta);
+            rng5.advance_by(delta);
+            for _ in 0..delta {
+                rng2.advance();
+                rng4.advance();
+                rng6.advance();
+            }
+            assert_eq!(rng1, rng2);
+            assert_eq!(rng3, rng4);
+            assert_eq!(rng5, rng6);
+        }
+    }
+
+    #[test]
+    fn advance_by_backwards() {
+        let mut rng = PCG32Fast::new(None);
+        rng.discard(REF_NUMBERS.len());
+        for x in REF_NUMBERS.iter().rev() {
+            rng.advance_by(-1);
+            assert_eq!(rng.get(), *x);
+            rng.advance_by(-1);
+        }
+        assert_eq!(rng, PCG32Fast::new(None));
+
+        let mut rng = PCG64::new(Some(42), Some(54));
+        let numbers: [u64; 8] = generate(&mut rng);
+        rng.advance_by(-(numbers.len() as i128));
+        assert_eq!(generate(&mut rng), numbers);
+    }
+
+    #[test]
+    fn advance_by_huge_offsets() {
+        const HUGE: i128 = 0x1234_5678_9abc_def0_1234;
+        let mut rng1 = PCG32Fast::new(None);
+        rng1.advance_by(HUGE);
+        rng1.advance_by(-HUGE);
+        assert_eq!(rng1, PCG32Fast::new(None));
+
+        // Jumping in several parts ends up at the same position as jumping at once.
+        let mut rng2 = PCG32Fast::new(None);
+        rng1.advance_by(HUGE);
+        rng2.advance_by(HUGE - 1_000_001);
+        rng2.advance_by(1_000_000);
+        rng2.get();
+        assert_eq!(rng1, rng2);
+
+        // The period divides 2^64.
+        rng2.advance_by(1 << 64);
+        assert_eq!(rng1, rng2);
+        rng2.advance_by(i128::MIN);
+        assert_eq!(rng1, rng2);
+
+        let mut rng3 = PCG64::new(None, None);
+        let mut rng4 = PCG64::new(None, None);
+        rng3.advance_by(i128::MAX);
+        rng3.advance_by(2);
+        rng4.advance_by(i128::MIN);
+        rng4.advance_by(1);
+        assert_eq!(rng3, rng4);
+    }
+
+    #[test]
+    fn random_number_engine_partial_eq() {
+        let mut rng1 = PCG32Fast::new(None);
+        let rng2 = PCG32Fast::new(None);
+        assert_eq!(rng1, rng2);
+        rng1.discard(1);
+        assert_ne!(rng1, rng2);
+    }
+}
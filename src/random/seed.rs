@@ -0,0 +1,102 @@
+use super::SplitMix64;
+use std::io::Read;
+
+/// A 64 bit seed for the random number engines that is easy to share between players.
+///
+/// Seeds are written as 16 hexadecimal digits in groups of four, e.g. `0123-4567-89ab-cdef`.
+/// When parsing, the dashes are optional, letters may be upper or lower case and leading zeros
+/// may be left out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Seed(pub u64);
+
+impl Seed {
+    /// Gets a seed from the operating system's entropy source.
+    ///
+    /// Falls back to mixing the current time and process id if `/dev/urandom` is unavailable.
+    pub fn from_entropy() -> Self {
+        Self::from_urandom().unwrap_or_else(|_| Self::from_time())
+    }
+
+    fn from_urandom() -> std::io::Result<Self> {
+        let mut bytes = [0u8; 8];
+        std::fs::File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+        Ok(Self(u64::from_ne_bytes(bytes)))
+    }
+
+    fn from_time() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        let mut splitmix = SplitMix64::new(Some(nanos ^ ((std::process::id() as u64) << 32)));
+        Self(splitmix.next())
+    }
+}
+
+impl std::fmt::Display for Seed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04x}-{:04x}-{:04x}-{:04x}",
+            self.0 >> 48,
+            (self.0 >> 32) & 0xffff,
+            (self.0 >> 16) & 0xffff,
+            self.0 & 0xffff
+        )
+    }
+}
+
+impl std::str::FromStr for Seed {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let digits: String = text.chars().filter(|c| *c != '-').collect();
+        if digits.is_empty() || digits.len() > 16 || !digits.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            return Err(format!("invalid seed: {}", text));
+        }
+        u64::from_str_radix(&digits, 16)
+            .map(Self)
+            .map_err(|_| format!("invalid seed: {}", text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(Seed(0x0123456789abcdef).to_string(), "0123-4567-89ab-cdef");
+        assert_eq!(Seed(0).to_string(), "0000-0000-0000-0000");
+        assert_eq!(Seed(u64::MAX).to_string(), "ffff-ffff-ffff-ffff");
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("0123-4567-89ab-cdef".parse(), Ok(Seed(0x0123456789abcdef)));
+        assert_eq!("0123456789ABCDEF".parse(), Ok(Seed(0x0123456789abcdef)));
+        assert_eq!("2a".parse(), Ok(Seed(0x2a)));
+        assert!("".parse::<Seed>().is_err());
+        assert!("-".parse::<Seed>().is_err());
+        assert!("+2a".parse::<Seed>().is_err());
+        assert!("0123-4567-89ab-cdef-0".parse::<Seed>().is_err());
+        assert!("seed".parse::<Seed>().is_err());
+    }
+
+    #[test]
+    fn parse_display_round_trip() {
+        let mut splitmix = SplitMix64::new(None);
+        for _ in 0..1000 {
+            let seed = Seed(splitmix.next());
+            assert_eq!(seed.to_string().parse(), Ok(seed));
+        }
+    }
+
+    #[test]
+    fn entropy_sources_differ() {
+        assert_ne!(Seed::from_entropy(), Seed::from_entropy());
+        let time_seed = Seed::from_time();
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        assert_ne!(Seed::from_time(), time_seed);
+    }
+}
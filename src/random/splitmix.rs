@@ -0,0 +1,146 @@
+use super::checkpoint::{expect_words, Checkpoint, CheckpointError};
+use super::{EngineKind, RandomNumberEngine, SeedFromU64, DEFAULT_SEED};
+
+/// Random number engine for 64 bit random numbers.
+///
+/// This is an implementation of the SplitMix64 PRNG from <https://prng.di.unimi.it>.
+/// The state is a simple counter, so the engine can jump to any position in constant time.
+/// It is mainly used to expand a single seed into the larger state of other engines.
+#[derive(Debug, PartialEq)]
+pub struct SplitMix64 {
+    state: <Self as RandomNumberEngine>::StateType,
+}
+
+impl RandomNumberEngine for SplitMix64 {
+    type ResultType = u64;
+    type StateType = u64;
+
+    const MIN: Self::ResultType = Self::ResultType::MIN;
+    const MAX: Self::ResultType = Self::ResultType::MAX;
+
+    fn seed(&mut self, seed: Option<Self::StateType>) {
+        self.state = seed.unwrap_or(DEFAULT_SEED);
+    }
+
+    fn state(&self) -> Self::StateType {
+        self.state
+    }
+
+    fn get(&mut self) -> Self::ResultType {
+        self.state = self.state.wrapping_add(Self::GAMMA);
+        Self::output(self.state)
+    }
+
+    fn discard(&mut self, z: usize) {
+        self.advance_by(z as i128);
+    }
+}
+
+impl SplitMix64 {
+    const GAMMA: u64 = 0x9e3779b97f4a7c15;
+
+    /// Constructs the engine with a default or optionally given seed.
+    pub const fn new(seed: Option<u64>) -> Self {
+        Self {
+            state: match seed {
+                Some(seed) => seed,
+                None => DEFAULT_SEED,
+            },
+        }
+    }
+
+    const fn output(state: u64) -> u64 {
+        let z = (state ^ (state >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        let z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Gets the next value in a const context.
+    pub(crate) const fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(Self::GAMMA);
+        Self::output(self.state)
+    }
+
+    /// Restores an engine from a value returned by `state()`.
+    pub fn from_state(state: u64) -> Self {
+        Self { state }
+    }
+
+    /// Advances the engine's state by `delta` steps in constant time.
+    ///
+    /// Negative values move the engine back.
+    pub fn advance_by(&mut self, delta: i128) {
+        self.state = self
+            .state
+            .wrapping_add(Self::GAMMA.wrapping_mul(delta as u64));
+    }
+}
+
+impl SeedFromU64 for SplitMix64 {
+    fn seed_from_u64(seed: u64) -> Self {
+        Self::new(Some(seed))
+    }
+}
+
+impl Checkpoint for SplitMix64 {
+    const KIND: EngineKind = EngineKind::SplitMix64;
+
+    fn to_words(&self) -> Vec<u64> {
+        vec![self.state]
+    }
+
+    fn from_words(words: &[u64]) -> Result<Self, CheckpointError> {
+        let [state] = expect_words(words)?;
+        Ok(Self::from_state(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_reference_implementation() {
+        let mut rng = SplitMix64::new(Some(0));
+        let numbers: Vec<u64> = (0..6).map(|_| rng.get()).collect();
+        assert_eq!(
+            numbers,
+            [
+                0xe220a8397b1dcdaf,
+                0x6e789e6aa1b965f4,
+                0x06c45d188009454f,
+                0xf88bb8a8724c81ec,
+                0x1b39896a51a8749b,
+                0x53cb9f0c747ea2ea
+            ]
+        );
+        let mut rng = SplitMix64::new(Some(1234567));
+        let numbers: Vec<u64> = (0..3).map(|_| rng.get()).collect();
+        assert_eq!(
+            numbers,
+            [0x599ed017fb08fc85, 0x2c73f08458540fa5, 0x883ebce5a3f27c77]
+        );
+    }
+
+    #[test]
+    fn random_number_engine_seed_and_state() {
+        let mut rng = SplitMix64::new(None);
+        rng.get();
+        rng.seed(Some(42));
+        assert_eq!(rng.state(), 42);
+        assert_eq!(rng, SplitMix64::new(Some(42)));
+    }
+
+    #[test]
+    fn random_number_engine_discard() {
+        let mut rng1 = SplitMix64::new(None);
+        let mut rng2 = SplitMix64::new(None);
+        rng1.discard(1000);
+        (0..1000).for_each(|_| {
+            rng2.get();
+        });
+        assert_eq!(rng1, rng2);
+        rng1.advance_by(-1000);
+        assert_eq!(rng1, SplitMix64::new(None));
+    }
+}
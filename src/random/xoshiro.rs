@@ -0,0 +1,281 @@
+use super::checkpoint::{expect_words, Checkpoint, CheckpointError};
+use super::{EngineKind, RandomNumberEngine, SeedFromU64, SplitMix64, DEFAULT_SEED};
+
+/// Expands a single 64 bit seed into the state of a xoshiro/xoroshiro engine.
+///
+/// This is the seeding procedure recommended by the authors, which also guarantees that the
+/// state is not all zero.
+const fn expand_seed<const N: usize>(seed: u64) -> [u64; N] {
+    let mut splitmix = SplitMix64::new(Some(seed));
+    let mut state = [0; N];
+    let mut i = 0;
+    while i < N {
+        state[i] = splitmix.next();
+        i += 1;
+    }
+    state
+}
+
+/// Random number engine for 64 bit random numbers.
+///
+/// This is an implementation of the xoshiro256** PRNG from <https://prng.di.unimi.it>.
+/// An all zero state is invalid and replaced by the default state.
+#[derive(Debug, PartialEq)]
+pub struct Xoshiro256StarStar {
+    state: <Self as RandomNumberEngine>::StateType,
+}
+
+impl RandomNumberEngine for Xoshiro256StarStar {
+    type ResultType = u64;
+    type StateType = [u64; 4];
+
+    const MIN: Self::ResultType = Self::ResultType::MIN;
+    const MAX: Self::ResultType = Self::ResultType::MAX;
+
+    fn seed(&mut self, seed: Option<Self::StateType>) {
+        *self = Self::new(seed);
+    }
+
+    fn state(&self) -> Self::StateType {
+        self.state
+    }
+
+    fn get(&mut self) -> Self::ResultType {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    fn discard(&mut self, z: usize) {
+        (0..z).for_each(|_| {
+            self.get();
+        });
+    }
+}
+
+impl Xoshiro256StarStar {
+    const DEFAULT_STATE: [u64; 4] = expand_seed(DEFAULT_SEED);
+
+    /// Constructs the engine with a default or optionally given state.
+    pub fn new(seed: Option<[u64; 4]>) -> Self {
+        Self {
+            state: seed
+                .filter(|state| *state != [0; 4])
+                .unwrap_or(Self::DEFAULT_STATE),
+        }
+    }
+
+    /// Restores an engine from a value returned by `state()`.
+    ///
+    /// Returns `None` for the invalid all zero state.
+    pub fn from_state(state: [u64; 4]) -> Option<Self> {
+        (state != [0; 4]).then_some(Self { state })
+    }
+}
+
+impl Checkpoint for Xoshiro256StarStar {
+    const KIND: EngineKind = EngineKind::Xoshiro256StarStar;
+
+    fn to_words(&self) -> Vec<u64> {
+        self.state.to_vec()
+    }
+
+    fn from_words(words: &[u64]) -> Result<Self, CheckpointError> {
+        Self::from_state(expect_words(words)?).ok_or(CheckpointError::InvalidState)
+    }
+}
+
+impl SeedFromU64 for Xoshiro256StarStar {
+    /// Constructs the engine from a single 64 bit seed expanded with [`SplitMix64`].
+    fn seed_from_u64(seed: u64) -> Self {
+        Self {
+            state: expand_seed(seed),
+        }
+    }
+}
+
+/// Random number engine for 64 bit random numbers.
+///
+/// This is an implementation of the xoroshiro128+ PRNG from <https://prng.di.unimi.it>
+/// using the 2018 shift constants. It is faster than [`Xoshiro256StarStar`], but its lowest bits
+/// have low linear complexity.
+/// An all zero state is invalid and replaced by the default state.
+#[derive(Debug, PartialEq)]
+pub struct Xoroshiro128Plus {
+    state: <Self as RandomNumberEngine>::StateType,
+}
+
+impl RandomNumberEngine for Xoroshiro128Plus {
+    type ResultType = u64;
+    type StateType = [u64; 2];
+
+    const MIN: Self::ResultType = Self::ResultType::MIN;
+    const MAX: Self::ResultType = Self::ResultType::MAX;
+
+    fn seed(&mut self, seed: Option<Self::StateType>) {
+        *self = Self::new(seed);
+    }
+
+    fn state(&self) -> Self::StateType {
+        self.state
+    }
+
+    fn get(&mut self) -> Self::ResultType {
+        let [s0, mut s1] = self.state;
+        let result = s0.wrapping_add(s1);
+        s1 ^= s0;
+        self.state = [s0.rotate_left(24) ^ s1 ^ (s1 << 16), s1.rotate_left(37)];
+        result
+    }
+
+    fn discard(&mut self, z: usize) {
+        (0..z).for_each(|_| {
+            self.get();
+        });
+    }
+}
+
+impl Xoroshiro128Plus {
+    const DEFAULT_STATE: [u64; 2] = expand_seed(DEFAULT_SEED);
+
+    /// Constructs the engine with a default or optionally given state.
+    pub fn new(seed: Option<[u64; 2]>) -> Self {
+        Self {
+            state: seed
+                .filter(|state| *state != [0; 2])
+                .unwrap_or(Self::DEFAULT_STATE),
+        }
+    }
+
+    /// Restores an engine from a value returned by `state()`.
+    ///
+    /// Returns `None` for the invalid all zero state.
+    pub fn from_state(state: [u64; 2]) -> Option<Self> {
+        (state != [0; 2]).then_some(Self { state })
+    }
+}
+
+impl Checkpoint for Xoroshiro128Plus {
+    const KIND: EngineKind = EngineKind::Xoroshiro128Plus;
+
+    fn to_words(&self) -> Vec<u64> {
+        self.state.to_vec()
+    }
+
+    fn from_words(words: &[u64]) -> Result<Self, CheckpointError> {
+        Self::from_state(expect_words(words)?).ok_or(CheckpointError::InvalidState)
+    }
+}
+
+impl SeedFromU64 for Xoroshiro128Plus {
+    /// Constructs the engine from a single 64 bit seed expanded with [`SplitMix64`].
+    fn seed_from_u64(seed: u64) -> Self {
+        Self {
+            state: expand_seed(seed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xoshiro256starstar_match_reference_implementation() {
+        let mut rng = Xoshiro256StarStar::new(Some([1, 2, 3, 4]));
+        let numbers: Vec<u64> = (0..6).map(|_| rng.get()).collect();
+        assert_eq!(
+            numbers,
+            [
+                0x2d00,
+                0x0,
+                0x5a007080,
+                0x10e0000000009d80,
+                0x10e0b61ce1009d80,
+                0x0870021ce143ad00
+            ]
+        );
+        let mut rng = Xoshiro256StarStar::new(None);
+        let numbers: Vec<u64> = (0..3).map(|_| rng.get()).collect();
+        assert_eq!(
+            numbers,
+            [0xd49e93a7d65eb256, 0xb91e44b95f3374ac, 0x531a252a360c6571]
+        );
+    }
+
+    #[test]
+    fn xoroshiro128plus_match_reference_implementation() {
+        let mut rng = Xoroshiro128Plus::new(Some([1, 2]));
+        let numbers: Vec<u64> = (0..6).map(|_| rng.get()).collect();
+        assert_eq!(
+            numbers,
+            [
+                0x3,
+                0x6001030003,
+                0x20c102c302000c03,
+                0x810180670d23ad61,
+                0x26d13a4941333a42,
+                0x538a501c02f58b2e
+            ]
+        );
+        let mut rng = Xoroshiro128Plus::new(None);
+        let numbers: Vec<u64> = (0..3).map(|_| rng.get()).collect();
+        assert_eq!(
+            numbers,
+            [0x7546493b1c249095, 0xe96f03e013b7f4bc, 0x9b4e76bb2d014d3e]
+        );
+    }
+
+    #[test]
+    fn zero_state_is_replaced() {
+        assert_eq!(
+            Xoshiro256StarStar::new(Some([0; 4])),
+            Xoshiro256StarStar::new(None)
+        );
+        assert_eq!(
+            Xoroshiro128Plus::new(Some([0; 2])),
+            Xoroshiro128Plus::new(None)
+        );
+        assert_eq!(
+            Xoshiro256StarStar::seed_from_u64(DEFAULT_SEED),
+            Xoshiro256StarStar::new(None)
+        );
+    }
+
+    #[test]
+    fn random_number_engine_seed_and_state() {
+        let mut rng = Xoshiro256StarStar::new(None);
+        rng.get();
+        rng.seed(Some([1, 2, 3, 4]));
+        assert_eq!(rng.state(), [1, 2, 3, 4]);
+        let mut rng = Xoroshiro128Plus::new(None);
+        rng.get();
+        rng.seed(Some([1, 2]));
+        assert_eq!(rng.state(), [1, 2]);
+    }
+
+    #[test]
+    fn random_number_engine_discard() {
+        let mut rng1 = Xoshiro256StarStar::new(None);
+        let mut rng2 = Xoshiro256StarStar::new(None);
+        rng1.discard(1000);
+        (0..1000).for_each(|_| {
+            rng2.get();
+        });
+        assert_eq!(rng1, rng2);
+        let mut rng1 = Xoroshiro128Plus::new(None);
+        let mut rng2 = Xoroshiro128Plus::new(None);
+        rng1.discard(1000);
+        (0..1000).for_each(|_| {
+            rng2.get();
+        });
+        assert_eq!(rng1, rng2);
+    }
+}
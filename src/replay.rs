@@ -0,0 +1,369 @@
+//! Saves games as the seed and the timestamped events that drove them.
+//!
+//! A replay starts with a header naming the random number engine, its seed and the terminal the
+//! game was played on, followed by one line per event holding its time since the start of the game
+//! in microseconds. Feeding the events to a new game in order re-simulates it exactly, so its
+//! frames can be rendered again offline without waiting in real time, e.g. into an asciicast.
+//!
+//! ```text
+//! rustsnake-replay 1
+//! rng pcg32-fast
+//! seed 0123-4567-89ab-cdef
+//! size 80 24
+//! capabilities erase-character
+//! timestamp 1700000000
+//! 0 tick
+//! 0 render 0 0 0
+//! 150000 input 77
+//! ```
+
+use crate::random::{EngineKind, Seed};
+use crate::scheduler::TickStats;
+use crate::terminal::Capabilities;
+use crate::types::Dimensions;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Starts the first line of every replay, followed by the version of the format.
+const MAGIC: &str = "rustsnake-replay";
+const VERSION: &str = "1";
+
+const ERASE_CHARACTER: &str = "erase-character";
+const REPEAT_CHARACTER: &str = "repeat-character";
+const SYNCHRONIZED_OUTPUT: &str = "synchronized-output";
+
+/// Everything a game depends on besides its events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub rng: EngineKind,
+    pub seed: Seed,
+    pub dimensions: Dimensions,
+    /// Capabilities the frames were encoded for.
+    pub capabilities: Capabilities,
+    /// Start of the game in seconds since the Unix epoch, if known.
+    pub timestamp: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// Bytes read from the terminal.
+    Input(Vec<u8>),
+    /// The game advanced by one step.
+    Tick,
+    /// A frame was rendered, with the timing of the most recent tick as shown by the overlay.
+    Render(TickStats),
+    /// A message was shown in the status line, e.g. after taking a screenshot.
+    Notice(String),
+}
+
+/// A saved game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub header: Header,
+    /// The events in the order they were handled, with their times since the start.
+    pub events: Vec<(Duration, Event)>,
+}
+
+/// Writes the events of a game as they happen.
+pub struct Writer<W: Write> {
+    writer: W,
+    line: String,
+}
+
+impl<W: Write> Writer<W> {
+    /// Starts a replay by writing the header.
+    pub fn new(writer: W, header: &Header) -> io::Result<Self> {
+        let mut replay = Self {
+            writer,
+            line: String::new(),
+        };
+        writeln!(replay.line, "{} {}", MAGIC, VERSION).unwrap();
+        writeln!(replay.line, "rng {}", header.rng).unwrap();
+        writeln!(replay.line, "seed {}", header.seed).unwrap();
+        writeln!(
+            replay.line,
+            "size {} {}",
+            header.dimensions.x, header.dimensions.y
+        )
+        .unwrap();
+        replay.line.push_str("capabilities");
+        let capabilities = &header.capabilities;
+        for (name, supported) in [
+            (ERASE_CHARACTER, capabilities.erase_character),
+            (REPEAT_CHARACTER, capabilities.repeat_character),
+            (SYNCHRONIZED_OUTPUT, capabilities.synchronized_output),
+        ] {
+            if supported {
+                write!(replay.line, " {}", name).unwrap();
+            }
+        }
+        replay.line.push('\n');
+        if let Some(timestamp) = header.timestamp {
+            writeln!(replay.line, "timestamp {}", timestamp).unwrap();
+        }
+        replay.write_line()?;
+        Ok(replay)
+    }
+
+    fn write_line(&mut self) -> io::Result<()> {
+        self.writer.write_all(self.line.as_bytes())?;
+        self.line.clear();
+        Ok(())
+    }
+
+    /// Records an event that happened at the given time since the start.
+    pub fn event(&mut self, time: Duration, event: &Event) -> io::Result<()> {
+        write!(self.line, "{}", time.as_micros()).unwrap();
+        match event {
+            Event::Input(bytes) => {
+                self.line.push_str(" input");
+                for byte in bytes {
+                    write!(self.line, " {:02x}", byte).unwrap();
+                }
+            }
+            Event::Tick => self.line.push_str(" tick"),
+            Event::Render(tick_stats) => write!(
+                self.line,
+                " render {} {} {}",
+                tick_stats.interval.as_micros(),
+                tick_stats.lateness.as_micros(),
+                tick_stats.overruns
+            )
+            .unwrap(),
+            Event::Notice(message) => {
+                self.line.push_str(" notice ");
+                // Keeps every event on a line of its own.
+                self.line.extend(
+                    message
+                        .chars()
+                        .map(|c| if c.is_control() { ' ' } else { c }),
+                );
+            }
+        }
+        self.line.push('\n');
+        self.write_line()
+    }
+
+    /// Flushes the replay and gets the writer back.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(text: Option<&str>) -> Result<T, String> {
+    let text = text.ok_or("missing value")?;
+    text.parse()
+        .map_err(|_| format!("invalid number: {}", text))
+}
+
+fn parse_duration(text: Option<&str>) -> Result<Duration, String> {
+    parse_number(text).map(Duration::from_micros)
+}
+
+fn parse_event(line: &str) -> Result<(Duration, Event), String> {
+    let (time, rest) = line.split_once(' ').ok_or("missing event")?;
+    let time = parse_duration(Some(time))?;
+    let (kind, arguments) = rest.split_once(' ').unwrap_or((rest, ""));
+    let mut values = arguments.split(' ').filter(|value| !value.is_empty());
+    let event = match kind {
+        "input" => Event::Input(
+            values
+                .by_ref()
+                .map(|value| {
+                    u8::from_str_radix(value, 16).map_err(|_| format!("invalid byte: {}", value))
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        "tick" => Event::Tick,
+        "render" => Event::Render(TickStats {
+            interval: parse_duration(values.next())?,
+            lateness: parse_duration(values.next())?,
+            overruns: parse_number(values.next())?,
+        }),
+        "notice" => return Ok((time, Event::Notice(arguments.to_string()))),
+        _ => return Err(format!("unknown event: {}", kind)),
+    };
+    match values.next() {
+        Some(value) => Err(format!("unexpected value: {}", value)),
+        None => Ok((time, event)),
+    }
+}
+
+fn parse_capabilities(names: &str) -> Result<Capabilities, String> {
+    let mut capabilities = Capabilities::default();
+    for name in names.split(' ').filter(|name| !name.is_empty()) {
+        match name {
+            ERASE_CHARACTER => capabilities.erase_character = true,
+            REPEAT_CHARACTER => capabilities.repeat_character = true,
+            SYNCHRONIZED_OUTPUT => capabilities.synchronized_output = true,
+            _ => return Err(format!("unknown capability: {}", name)),
+        }
+    }
+    Ok(capabilities)
+}
+
+fn parse_header<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Result<Header, String> {
+    let mut value = |key: &str| {
+        lines
+            .next()
+            .and_then(|line| line.strip_prefix(key))
+            .and_then(|rest| rest.strip_prefix(' ').or(rest.is_empty().then_some("")))
+            .ok_or_else(|| format!("expected {}", key))
+    };
+    if value(MAGIC)? != VERSION {
+        return Err("unsupported replay version".to_string());
+    }
+    let rng = value("rng")?.parse()?;
+    let seed = value("seed")?.parse()?;
+    let mut size = value("size")?.split(' ');
+    let dimensions = Dimensions::new(parse_number(size.next())?, parse_number(size.next())?);
+    let capabilities = parse_capabilities(value("capabilities")?)?;
+    Ok(Header {
+        rng,
+        seed,
+        dimensions,
+        capabilities,
+        timestamp: None,
+    })
+}
+
+impl std::str::FromStr for Replay {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut lines = text.lines().peekable();
+        let mut header = parse_header(&mut lines)?;
+        if let Some(timestamp) = lines
+            .peek()
+            .and_then(|line| line.strip_prefix("timestamp "))
+        {
+            header.timestamp = Some(parse_number(Some(timestamp))?);
+            lines.next();
+        }
+        let events = lines
+            .enumerate()
+            .map(|(i, line)| {
+                parse_event(line).map_err(|error| format!("event {}: {}", i + 1, error))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { header, events })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> Header {
+        Header {
+            rng: EngineKind::Xoshiro256StarStar,
+            seed: Seed(0x0123456789abcdef),
+            dimensions: Dimensions::new(80, 24),
+            capabilities: Capabilities {
+                erase_character: true,
+                repeat_character: false,
+                synchronized_output: true,
+            },
+            timestamp: Some(1700000000),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let events = vec![
+            (Duration::ZERO, Event::Tick),
+            (
+                Duration::from_micros(20),
+                Event::Render(TickStats {
+                    interval: Duration::from_micros(100_001),
+                    lateness: Duration::from_micros(12),
+                    overruns: 3,
+                }),
+            ),
+            (
+                Duration::from_millis(150),
+                Event::Input(b"w\x1b[A".to_vec()),
+            ),
+            (Duration::from_millis(160), Event::Input(Vec::new())),
+            (
+                Duration::from_secs(2),
+                Event::Notice(" Saved a\nb ".to_string()),
+            ),
+        ];
+        let mut writer = Writer::new(Vec::new(), &header()).unwrap();
+        for (time, event) in &events {
+            writer.event(*time, event).unwrap();
+        }
+        let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(
+            text,
+            "rustsnake-replay 1\n\
+             rng xoshiro256starstar\n\
+             seed 0123-4567-89ab-cdef\n\
+             size 80 24\n\
+             capabilities erase-character synchronized-output\n\
+             timestamp 1700000000\n\
+             0 tick\n\
+             20 render 100001 12 3\n\
+             150000 input 77 1b 5b 41\n\
+             160000 input\n\
+             2000000 notice  Saved a b \n"
+        );
+
+        let replay: Replay = text.parse().unwrap();
+        assert_eq!(replay.header, header());
+        assert_eq!(replay.events[..4], events[..4]);
+        assert_eq!(replay.events[4].1, Event::Notice(" Saved a b ".to_string()));
+    }
+
+    #[test]
+    fn optional_header_fields() {
+        let header = Header {
+            capabilities: Capabilities::default(),
+            timestamp: None,
+            ..header()
+        };
+        let writer = Writer::new(Vec::new(), &header).unwrap();
+        let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert!(text.contains("\ncapabilities\n"));
+        let replay: Replay = text.parse().unwrap();
+        assert_eq!(replay.header, header);
+        assert!(replay.events.is_empty());
+    }
+
+    #[test]
+    fn invalid_replays() {
+        let header = "rustsnake-replay 1\nrng pcg32\nseed 1\nsize 10 5\ncapabilities\n";
+        assert!(format!("{}0 tick\n", header).parse::<Replay>().is_ok());
+        for (text, error) in [
+            ("", "expected rustsnake-replay"),
+            ("rustsnake-replay 2\n", "unsupported replay version"),
+            ("rustsnake-replay 1\nseed 1\n", "expected rng"),
+            (
+                "rustsnake-replay 1\nrng pcg32\nseed 1\nsize 10\n",
+                "missing value",
+            ),
+            (
+                "rustsnake-replay 1\nrng pcg32\nseed 1\nsize 10 5\ncapabilities blink\n",
+                "unknown capability: blink",
+            ),
+        ] {
+            assert_eq!(text.parse::<Replay>(), Err(error.to_string()));
+        }
+        for (event, error) in [
+            ("tick", "event 1: missing event"),
+            ("x tick", "event 1: invalid number: x"),
+            ("0 jump", "event 1: unknown event: jump"),
+            ("0 input 100", "event 1: invalid byte: 100"),
+            ("0 render 1 2", "event 1: missing value"),
+            ("0 tick 1", "event 1: unexpected value: 1"),
+        ] {
+            assert_eq!(
+                format!("{}{}\n", header, event).parse::<Replay>(),
+                Err(error.to_string())
+            );
+        }
+    }
+}
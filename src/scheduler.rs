@@ -0,0 +1,167 @@
+use std::time::{Duration, Instant};
+
+/// Timing model that determines how long a single move of the snake takes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TickTiming {
+    /// Every move takes the same time regardless of its direction.
+    Uniform,
+    /// Vertical moves take `vertical_ratio` times as long as horizontal moves.
+    ///
+    /// Terminal cells are usually about twice as high as wide, so a ratio of about 2 makes the
+    /// snake appear to move at the same visual speed in all directions.
+    AspectCorrected { vertical_ratio: f64 },
+}
+
+impl TickTiming {
+    /// Gets the duration of a move given the duration of a horizontal move.
+    pub fn step(&self, base: Duration, vertical: bool) -> Duration {
+        match self {
+            TickTiming::AspectCorrected { vertical_ratio } if vertical => {
+                base.mul_f64(*vertical_ratio)
+            }
+            _ => base,
+        }
+    }
+}
+
+/// Timing statistics of the most recent tick.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TickStats {
+    /// Time between the two most recent ticks.
+    pub interval: Duration,
+    /// Time by which the most recent tick missed its deadline.
+    pub lateness: Duration,
+    /// Total number of ticks that were dropped because the scheduler fell behind.
+    pub overruns: u64,
+}
+
+/// Fixed-timestep scheduler.
+///
+/// Ticks are scheduled at absolute deadlines relative to the previous tick's deadline instead of
+/// the time the previous tick was processed, so the time spent rendering and handling input does
+/// not add up. The step may change between ticks, which is what allows direction-dependent tick
+/// durations.
+pub struct Scheduler {
+    last_tick: Instant,
+    last_fired: Option<Instant>,
+    stats: TickStats,
+}
+
+impl Scheduler {
+    pub fn new(start: Instant) -> Self {
+        Self {
+            last_tick: start,
+            last_fired: None,
+            stats: TickStats::default(),
+        }
+    }
+
+    /// Checks if the next tick is due and advances the scheduler if it is.
+    ///
+    /// If the scheduler lags behind by more than a whole step, the missed ticks are counted as
+    /// overruns and the scheduler is resynchronized to `now` instead of firing them in a burst.
+    pub fn poll(&mut self, now: Instant, step: Duration) -> bool {
+        let deadline = self.last_tick + step;
+        if now < deadline {
+            return false;
+        }
+        let lateness = now - deadline;
+        self.last_tick = if lateness > step {
+            self.stats.overruns += (lateness.as_nanos() / step.as_nanos().max(1)) as u64;
+            now
+        } else {
+            deadline
+        };
+        self.stats.lateness = lateness;
+        self.stats.interval = self.last_fired.map_or(step, |last_fired| now - last_fired);
+        self.last_fired = Some(now);
+        true
+    }
+
+    /// Gets the timing statistics of the most recent tick.
+    pub fn stats(&self) -> &TickStats {
+        &self.stats
+    }
+
+    /// Gets the time left until the next tick is due.
+    pub fn remaining(&self, now: Instant, step: Duration) -> Duration {
+        (self.last_tick + step).saturating_duration_since(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn uniform_timing() {
+        let timing = TickTiming::Uniform;
+        assert_eq!(timing.step(STEP, false), STEP);
+        assert_eq!(timing.step(STEP, true), STEP);
+    }
+
+    #[test]
+    fn aspect_corrected_timing() {
+        let timing = TickTiming::AspectCorrected {
+            vertical_ratio: 2.0,
+        };
+        assert_eq!(timing.step(STEP, false), STEP);
+        assert_eq!(timing.step(STEP, true), STEP * 2);
+    }
+
+    #[test]
+    fn poll() {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new(start);
+        assert!(!scheduler.poll(start, STEP));
+        assert_eq!(scheduler.remaining(start, STEP), STEP);
+        assert!(scheduler.poll(start + STEP, STEP));
+        assert!(!scheduler.poll(start + STEP, STEP));
+    }
+
+    #[test]
+    fn poll_does_not_drift() {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new(start);
+        // The tick is processed late, the next deadline stays on the fixed grid.
+        assert!(scheduler.poll(start + STEP + STEP / 4, STEP));
+        assert_eq!(
+            scheduler.remaining(start + STEP + STEP / 4, STEP),
+            STEP * 3 / 4
+        );
+        assert!(scheduler.poll(start + STEP * 2, STEP));
+    }
+
+    #[test]
+    fn poll_resynchronizes() {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new(start);
+        assert!(scheduler.poll(start + STEP * 5, STEP));
+        assert!(!scheduler.poll(start + STEP * 5, STEP));
+        assert_eq!(scheduler.remaining(start + STEP * 5, STEP), STEP);
+        assert_eq!(scheduler.stats().overruns, 4);
+    }
+
+    #[test]
+    fn stats() {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new(start);
+        assert_eq!(*scheduler.stats(), TickStats::default());
+        assert!(scheduler.poll(start + STEP + STEP / 4, STEP));
+        assert_eq!(scheduler.stats().lateness, STEP / 4);
+        assert_eq!(scheduler.stats().overruns, 0);
+        assert!(scheduler.poll(start + STEP * 2, STEP));
+        assert_eq!(scheduler.stats().lateness, Duration::ZERO);
+        assert_eq!(scheduler.stats().interval, STEP * 3 / 4);
+    }
+
+    #[test]
+    fn poll_with_changing_step() {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new(start);
+        assert!(!scheduler.poll(start + STEP, STEP * 2));
+        assert!(scheduler.poll(start + STEP, STEP));
+    }
+}
@@ -1,77 +1,128 @@
-#![allow(non_camel_case_types)]
+pub mod ffi;
 
 use crate::frame_buffer::{Color, Pixel};
 use crate::types::{Dimensions, Position};
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
 
-type c_int = i32;
-#[cfg(target_os = "macos")]
-type c_ulong = u64;
-#[cfg(not(target_os = "macos"))]
-type c_uint = u32;
-type c_uchar = u8;
-
-#[cfg(target_os = "macos")]
-pub type tcflag_t = c_ulong;
-#[cfg(not(target_os = "macos"))]
-pub type tcflag_t = c_uint;
-type cc_t = c_uchar;
-
-const NCCS: usize = 32;
-const ECHO: tcflag_t = 0o000010;
-#[cfg(target_os = "macos")]
-const ICANON: tcflag_t = 0x0000100;
-#[cfg(not(target_os = "macos"))]
-const ICANON: tcflag_t = 0o0000002;
-
-#[repr(C)]
-struct Termios {
-    c_iflag: tcflag_t,  // input modes
-    c_oflag: tcflag_t,  // output modes
-    c_cflag: tcflag_t,  // control modes
-    c_lflag: tcflag_t,  // local modes
-    c_cc: [cc_t; NCCS], // special characters
-}
-
-#[link(name = "c")]
-extern "C" {
-    fn ioctl(fildes: c_int, request: c_int, ...) -> c_int;
-    fn tcgetattr(fd: c_int, termios_p: *mut Termios) -> c_int;
-    fn tcsetattr(fd: c_int, optional_actions: c_int, termios_p: *const Termios) -> c_int;
+const ESC: u8 = 0x1b;
+
+pub fn get_dimensions() -> io::Result<Dimensions> {
+    let winsize = ffi::get_winsize(io::stdin().as_raw_fd())?;
+    Ok(Dimensions {
+        x: winsize.ws_col as usize,
+        y: winsize.ws_row as usize,
+    })
 }
 
-const ESC: u8 = 0x1b;
+/// Non-blocking reader for terminal input.
+///
+/// Waits for input using `poll` so the caller can wait for either the next key press or a
+/// timeout, whichever comes first, without a dedicated reader thread.
+pub struct Input {
+    fd: RawFd,
+    buffer: [u8; 64],
+}
 
-pub fn get_dimensions() -> Result<Dimensions, &'static str> {
-    use std::mem;
+impl Input {
+    pub fn new() -> Self {
+        Self {
+            fd: io::stdin().as_raw_fd(),
+            buffer: [0; 64],
+        }
+    }
 
-    #[cfg(target_os = "macos")]
-    const TIOCGWINSZ: i32 = 0x40087468;
-    #[cfg(not(target_os = "macos"))]
-    const TIOCGWINSZ: i32 = 0x5413;
+    /// Waits until input is available or the timeout elapses and reads all available bytes.
+    ///
+    /// Waits indefinitely if no timeout is given. Returns an empty slice on timeout and an error
+    /// if the input was closed.
+    pub fn read(&mut self, timeout: Option<std::time::Duration>) -> io::Result<&[u8]> {
+        let timeout_ms = match timeout {
+            // Round up so a timeout below one millisecond does not turn into busy waiting.
+            Some(timeout) => timeout.as_micros().div_ceil(1000).min(i32::MAX as u128) as i32,
+            None => -1,
+        };
+        let mut poll_fd = ffi::PollFd {
+            fd: self.fd,
+            events: ffi::POLLIN,
+            revents: 0,
+        };
+        match ffi::poll(std::slice::from_mut(&mut poll_fd), timeout_ms) {
+            Ok(0) => return Ok(&[]),
+            Ok(_) => {}
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => return Ok(&[]),
+            Err(error) => return Err(error),
+        }
+        match ffi::read(self.fd, &mut self.buffer) {
+            Ok(0) => Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(count) => Ok(&self.buffer[..count]),
+            Err(error)
+                if error.kind() == io::ErrorKind::Interrupted
+                    || error.kind() == io::ErrorKind::WouldBlock =>
+            {
+                Ok(&[])
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
 
-    struct Winsize {
-        ws_row: u16,
-        ws_col: u16,
-        _ws_xpixel: u16, // unused
-        _ws_ypixel: u16, // unused
+impl std::default::Default for Input {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    use std::os::unix::io::AsRawFd;
-    let stdin_fd = std::io::stdin().as_raw_fd();
+/// Unbuffered writer for terminal output.
+///
+/// Writes straight to the file descriptor of standard output, so a frame reaches the terminal in
+/// a single `write` where possible instead of being split up by the buffer of `std::io::Stdout`.
+/// Interrupted writes are retried, and if the descriptor is non-blocking, a full output queue is
+/// waited out with `poll` instead of failing with `WouldBlock`.
+pub struct Output {
+    fd: RawFd,
+}
 
-    let winsize = unsafe {
-        let mut winsize = mem::MaybeUninit::<Winsize>::uninit();
-        let res = ioctl(stdin_fd, TIOCGWINSZ, winsize.as_mut_ptr());
-        if res == -1 {
-            return Err("Could not get terminal dimensions.");
+impl Output {
+    pub fn new() -> Self {
+        Self {
+            fd: io::stdout().as_raw_fd(),
         }
-        winsize.assume_init()
-    };
+    }
 
-    Ok(Dimensions {
-        x: winsize.ws_col as usize,
-        y: winsize.ws_row as usize,
-    })
+    fn wait_writable(&self) -> io::Result<()> {
+        let mut poll_fd = ffi::PollFd {
+            fd: self.fd,
+            events: ffi::POLLOUT,
+            revents: 0,
+        };
+        match ffi::poll(std::slice::from_mut(&mut poll_fd), -1) {
+            Err(error) if error.kind() != io::ErrorKind::Interrupted => Err(error),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::default::Default for Output {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl io::Write for Output {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        loop {
+            match ffi::write(self.fd, buffer) {
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => self.wait_writable()?,
+                result => return result,
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 impl Color {
@@ -98,13 +149,22 @@ impl Color {
 }
 
 impl Pixel {
+    /// Encodes the character followed by its combining marks.
     pub fn encode_ascii(&self, buffer: &mut [u8]) -> usize {
-        let res = self.character.encode_utf8(buffer);
-        res.len()
+        let mut len = self.character.encode_utf8(buffer).len();
+        for mark in self.marks.iter() {
+            len += mark.encode_utf8(&mut buffer[len..]).len();
+        }
+        len
     }
 }
 
 impl Position {
+    /// Gets the number of bytes `encode_ascii` writes.
+    pub fn encoded_len(&self) -> usize {
+        4 + decimal_len(self.y + 1) + decimal_len(self.x + 1)
+    }
+
     pub fn encode_ascii(&self, buffer: &mut [u8]) -> usize {
         buffer[0] = ESC;
         buffer[1] = 0x5b;
@@ -126,35 +186,341 @@ impl Position {
     }
 }
 
-pub fn reset() {
-    print!("\x1bc");
+fn decimal_len(value: usize) -> usize {
+    value.checked_ilog10().unwrap_or(0) as usize + 1
+}
+
+/// Control functions with a repetition count used to update the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlFunction {
+    /// `CUU`: moves the cursor up.
+    CursorUp(usize),
+    /// `CUD`: moves the cursor down.
+    CursorDown(usize),
+    /// `CUF`: moves the cursor right.
+    CursorForward(usize),
+    /// `CUB`: moves the cursor left.
+    CursorBackward(usize),
+    /// `ECH`: blanks characters from the cursor on without moving it.
+    EraseCharacter(usize),
+    /// `REP`: repeats the preceding character.
+    Repeat(usize),
+}
+
+impl ControlFunction {
+    fn parts(&self) -> (usize, u8) {
+        match *self {
+            ControlFunction::CursorUp(count) => (count, b'A'),
+            ControlFunction::CursorDown(count) => (count, b'B'),
+            ControlFunction::CursorForward(count) => (count, b'C'),
+            ControlFunction::CursorBackward(count) => (count, b'D'),
+            ControlFunction::EraseCharacter(count) => (count, b'X'),
+            ControlFunction::Repeat(count) => (count, b'b'),
+        }
+    }
+
+    /// Gets the number of bytes `encode_ascii` writes.
+    pub fn encoded_len(&self) -> usize {
+        match self.parts() {
+            (1, _) => 3,
+            (count, _) => 3 + decimal_len(count),
+        }
+    }
+
+    /// Encodes the function, leaving out a count of one since it is the default.
+    pub fn encode_ascii(&self, buffer: &mut [u8]) -> usize {
+        let (count, final_byte) = self.parts();
+        buffer[0] = ESC;
+        buffer[1] = 0x5b;
+        let mut i: usize = 2;
+        if count != 1 {
+            for c in count.to_string().bytes() {
+                buffer[i] = c;
+                i += 1;
+            }
+        }
+        buffer[i] = final_byte;
+        i + 1
+    }
+}
+
+/// Optional control functions of the terminal that make screen updates shorter.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Supports `ECH` to blank runs of cells.
+    pub erase_character: bool,
+    /// Supports `REP` to repeat runs of the same character.
+    pub repeat_character: bool,
+    /// Supports synchronized updates (DEC private mode 2026), which keep half drawn frames from
+    /// being shown.
+    pub synchronized_output: bool,
 }
 
-pub fn hide_cursor() {
-    print!("\x1b\x5b?25l");
+impl Capabilities {
+    /// Guesses the capabilities from the terminal type.
+    ///
+    /// `ECH` dates back to the VT220 and is supported nearly everywhere. `REP` is only used with
+    /// terminals known to support it, since many others claim to be an xterm without doing so.
+    pub fn from_term(term: &str, xterm_version: Option<&str>) -> Self {
+        let name = term.split('-').next().unwrap_or_default();
+        Self {
+            erase_character: !matches!(name, "" | "dumb"),
+            repeat_character: matches!(name, "foot" | "alacritty")
+                || (name == "xterm" && xterm_version.is_some()),
+            synchronized_output: false,
+        }
+    }
+
+    /// Gets the capabilities of the terminal given by the `TERM` environment variable.
+    pub fn detect() -> Self {
+        let term = std::env::var("TERM").unwrap_or_default();
+        let xterm_version = std::env::var("XTERM_VERSION").ok();
+        Self::from_term(&term, xterm_version.as_deref())
+    }
 }
 
-pub fn set_mode(enable: bool) {
-    use std::os::unix::io::AsRawFd;
-    let stdin_fd = std::io::stdin().as_raw_fd();
-    let mut termios = unsafe {
-        let mut termios = std::mem::MaybeUninit::<Termios>::uninit();
-        let res = tcgetattr(stdin_fd, termios.as_mut_ptr());
-        if res != 0 {
-            panic!("tcgetattr failed.");
+/// Starts a synchronized update: the terminal keeps showing the previous frame until it ends.
+pub const BEGIN_SYNCHRONIZED_UPDATE: &[u8] = b"\x1b\x5b?2026h";
+/// Ends a synchronized update and shows everything written since it began at once.
+pub const END_SYNCHRONIZED_UPDATE: &[u8] = b"\x1b\x5b?2026l";
+
+/// Asks the terminal whether it supports synchronized updates.
+///
+/// Requests the state of mode 2026 with `DECRQM`, followed by the primary device attributes. Every
+/// terminal answers the latter, so terminals that ignore `DECRQM` are recognized without waiting
+/// for the timeout. Input other than the answers is discarded. Requires raw mode.
+pub fn query_synchronized_output(
+    input: &mut Input,
+    timeout: std::time::Duration,
+) -> io::Result<bool> {
+    use std::io::Write;
+    io::stdout().flush()?;
+    Output::new().write_all(b"\x1b\x5b?2026$p\x1b\x5bc")?;
+    let deadline = std::time::Instant::now() + timeout;
+    let mut response = Vec::new();
+    while !contains_device_attributes(&response) {
+        let now = std::time::Instant::now();
+        if now >= deadline {
+            break;
         }
-        termios.assume_init()
-    };
+        response.extend_from_slice(input.read(Some(deadline - now))?);
+    }
+    // 1 and 2 mean set and reset, 3 permanently set; 0 and 4 mean the mode can't be used.
+    Ok(matches!(mode_report(&response, 2026), Some(1..=3)))
+}
 
-    if enable {
-        termios.c_lflag |= ECHO | ICANON;
-    } else {
-        termios.c_lflag &= !(ECHO | ICANON);
+/// Finds the report of a DEC private mode (`ESC [ ? mode ; value $ y`) and gets its value.
+fn mode_report(response: &[u8], mode: u16) -> Option<u8> {
+    let prefix = format!("\x1b\x5b?{};", mode);
+    let start = response
+        .windows(prefix.len())
+        .position(|window| window == prefix.as_bytes())?
+        + prefix.len();
+    let rest = &response[start..];
+    let digits = rest.iter().take_while(|byte| byte.is_ascii_digit()).count();
+    match rest[digits..].starts_with(b"$y") {
+        true => std::str::from_utf8(&rest[..digits]).ok()?.parse().ok(),
+        false => None,
     }
-    unsafe {
-        let res = tcsetattr(stdin_fd, 0, &termios);
-        if res != 0 {
-            panic!("tcsetattr failed.");
+}
+
+/// Checks whether a response contains the primary device attributes (`ESC [ ? ... c`).
+fn contains_device_attributes(response: &[u8]) -> bool {
+    (0..response.len()).any(|start| {
+        let Some(rest) = response[start..].strip_prefix(b"\x1b\x5b?") else {
+            return false;
+        };
+        rest.iter()
+            .find(|byte| !byte.is_ascii_digit() && **byte != b';')
+            .is_some_and(|byte| *byte == b'c')
+    })
+}
+
+/// Shows the game on the alternate screen buffer with a hidden cursor for as long as the value
+/// is alive.
+///
+/// The user's terminal content is left untouched. When the value is dropped, also while
+/// unwinding from a panic, the main screen buffer and the cursor are restored. Errors are ignored
+/// then, since the terminal may be gone already.
+pub struct AlternateScreen {
+    output: Output,
+}
+
+impl AlternateScreen {
+    pub fn enter() -> io::Result<Self> {
+        use std::io::Write;
+        // Output queued by `print!` has to reach the main screen first.
+        io::stdout().flush()?;
+        Self::enter_with(Output::new())
+    }
+
+    fn enter_with(mut output: Output) -> io::Result<Self> {
+        use std::io::Write;
+        output.write_all(b"\x1b\x5b?1049h\x1b\x5b2J\x1b\x5b?25l")?;
+        Ok(Self { output })
+    }
+}
+
+impl std::ops::Drop for AlternateScreen {
+    fn drop(&mut self) {
+        use std::io::Write;
+        let _ = io::stdout().flush();
+        let _ = self
+            .output
+            .write_all(b"\x1b\x5b0m\x1b\x5b?25h\x1b\x5b?1049l");
+    }
+}
+
+/// Puts the terminal into raw mode for as long as the value is alive.
+///
+/// Raw mode is equivalent to `cfmakeraw`: input is passed through byte by byte without echo,
+/// line editing, signal generation (Ctrl-C, Ctrl-Z) or flow control (Ctrl-S, Ctrl-Q), and output
+/// post-processing is disabled. A read returns as soon as a single byte is available.
+/// The original terminal attributes are restored when the value is dropped.
+pub struct RawMode {
+    fd: RawFd,
+    original: ffi::Termios,
+}
+
+impl RawMode {
+    pub fn enable() -> io::Result<Self> {
+        use ffi::*;
+
+        let fd = io::stdin().as_raw_fd();
+        let original = tcgetattr(fd)?;
+        let mut termios = original;
+        termios.c_iflag &= !(IGNBRK | BRKINT | PARMRK | ISTRIP | INLCR | IGNCR | ICRNL | IXON);
+        termios.c_oflag &= !OPOST;
+        termios.c_lflag &= !(ECHO | ECHONL | ICANON | ISIG | IEXTEN);
+        termios.c_cflag &= !(CSIZE | PARENB);
+        termios.c_cflag |= CS8;
+        termios.c_cc[VMIN] = 1;
+        termios.c_cc[VTIME] = 0;
+        tcsetattr(fd, TCSAFLUSH, &termios)?;
+        Ok(Self { fd, original })
+    }
+}
+
+impl std::ops::Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = ffi::tcsetattr(self.fd, ffi::TCSAFLUSH, &self.original);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(function: ControlFunction) -> String {
+        let mut buffer = [0; 16];
+        let len = function.encode_ascii(&mut buffer);
+        assert_eq!(len, function.encoded_len());
+        String::from_utf8(buffer[..len].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn control_functions() {
+        assert_eq!(encode(ControlFunction::CursorUp(1)), "\x1b[A");
+        assert_eq!(encode(ControlFunction::CursorDown(12)), "\x1b[12B");
+        assert_eq!(encode(ControlFunction::CursorForward(9)), "\x1b[9C");
+        assert_eq!(encode(ControlFunction::CursorBackward(100)), "\x1b[100D");
+        assert_eq!(encode(ControlFunction::EraseCharacter(3)), "\x1b[3X");
+        assert_eq!(encode(ControlFunction::Repeat(10)), "\x1b[10b");
+    }
+
+    #[test]
+    fn position_encoded_len() {
+        for position in [
+            Position::new(0, 0),
+            Position::new(9, 99),
+            Position::new(120, 8),
+        ] {
+            let mut buffer = [0; 16];
+            assert_eq!(position.encode_ascii(&mut buffer), position.encoded_len());
         }
     }
+
+    #[test]
+    fn capabilities_from_term() {
+        let capabilities = Capabilities::from_term("xterm-256color", None);
+        assert!(capabilities.erase_character && !capabilities.repeat_character);
+        let capabilities = Capabilities::from_term("xterm-256color", Some("XTerm(390)"));
+        assert!(capabilities.erase_character && capabilities.repeat_character);
+        let capabilities = Capabilities::from_term("foot", None);
+        assert!(capabilities.erase_character && capabilities.repeat_character);
+        assert_eq!(
+            Capabilities::from_term("dumb", None),
+            Capabilities::default()
+        );
+        assert_eq!(Capabilities::from_term("", None), Capabilities::default());
+    }
+
+    #[test]
+    fn synchronized_output_responses() {
+        let supported = b"\x1b[?2026;2$y\x1b[?62;22c";
+        assert_eq!(mode_report(supported, 2026), Some(2));
+        assert!(contains_device_attributes(supported));
+        assert_eq!(mode_report(b"\x1b[?2026;0$y", 2026), Some(0));
+        assert_eq!(mode_report(b"\x1b[?2026;1$y", 1049), None);
+        // The reply is incomplete.
+        assert_eq!(mode_report(b"\x1b[?2026;1", 2026), None);
+        assert!(!contains_device_attributes(b"\x1b[?2026;1$y\x1b[?62;"));
+        // Only the device attributes were sent back.
+        let unsupported = b"w\x1b[?64;1;2;6;22c";
+        assert_eq!(mode_report(unsupported, 2026), None);
+        assert!(contains_device_attributes(unsupported));
+    }
+
+    #[test]
+    fn alternate_screen_is_left_on_panic() {
+        let (master, slave) = ffi::openpty(None).unwrap();
+        let result = std::panic::catch_unwind(|| {
+            let _screen = AlternateScreen::enter_with(Output {
+                fd: slave.as_raw_fd(),
+            })
+            .unwrap();
+            panic!("game over");
+        });
+        assert!(result.is_err());
+        let mut buffer = [0u8; 64];
+        let count = ffi::read(master.as_raw_fd(), &mut buffer).unwrap();
+        assert_eq!(
+            &buffer[..count],
+            b"\x1b[?1049h\x1b[2J\x1b[?25l\x1b[0m\x1b[?25h\x1b[?1049l"
+        );
+    }
+
+    #[test]
+    fn alternate_screen_ignores_errors_on_drop() {
+        let (master, slave) = ffi::openpty(None).unwrap();
+        let screen = AlternateScreen::enter_with(Output {
+            fd: slave.as_raw_fd(),
+        })
+        .unwrap();
+        // Writing to a terminal fails once it is hung up.
+        drop(master);
+        drop(screen);
+    }
+
+    #[test]
+    fn output_waits_until_writable() {
+        use std::io::{Read, Write};
+        use std::os::unix::net::UnixStream;
+
+        let (writer, mut reader) = UnixStream::pair().unwrap();
+        writer.set_nonblocking(true).unwrap();
+        let data: Vec<u8> = (0..1 << 20).map(|i| i as u8).collect();
+        let expected = data.clone();
+        let reading = std::thread::spawn(move || {
+            let mut received = Vec::new();
+            reader.read_to_end(&mut received).unwrap();
+            received
+        });
+        let mut output = Output {
+            fd: writer.as_raw_fd(),
+        };
+        output.write_all(&data).unwrap();
+        drop(writer);
+        assert_eq!(reading.join().unwrap(), expected);
+    }
 }
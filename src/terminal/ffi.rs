@@ -0,0 +1,408 @@
+#![allow(non_camel_case_types)]
+
+// Bindings to the parts of libc required to control the terminal.
+//
+// The layouts follow the C headers of the respective targets. On Linux only the architectures
+// using the generic kernel ABI, which glibc and musl share, are supported. Alpha, MIPS, PowerPC
+// and SPARC have their own `ioctl` numbers, flag values and `c_cc` indices, and PowerPC also
+// orders the fields of `termios` differently, so building for them fails rather than silently
+// passing the wrong values. macOS and the BSDs share the flag values and only differ in the width
+// of `tcflag_t` and `speed_t`.
+
+use std::ffi::{c_int, c_short, c_uchar, c_uint, c_ulong, c_void};
+use std::io;
+use std::os::unix::io::{FromRawFd, OwnedFd, RawFd};
+
+pub type cc_t = c_uchar;
+
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        target_arch = "arm",
+        target_arch = "aarch64",
+        target_arch = "riscv32",
+        target_arch = "riscv64",
+        target_arch = "loongarch64",
+        target_arch = "s390x"
+    ))
+))]
+compile_error!("the terminal bindings only support Linux architectures using the generic ABI");
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod os {
+    use super::*;
+
+    pub type tcflag_t = c_uint;
+    pub type speed_t = c_uint;
+    #[cfg(target_env = "gnu")]
+    pub type ioctl_request_t = c_ulong;
+    #[cfg(not(target_env = "gnu"))]
+    pub type ioctl_request_t = c_int;
+    #[cfg(target_os = "android")]
+    pub type nfds_t = c_uint;
+    #[cfg(not(target_os = "android"))]
+    pub type nfds_t = c_ulong;
+
+    #[cfg(target_os = "android")]
+    pub const NCCS: usize = 19;
+    #[cfg(not(target_os = "android"))]
+    pub const NCCS: usize = 32;
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Termios {
+        pub c_iflag: tcflag_t,  // input modes
+        pub c_oflag: tcflag_t,  // output modes
+        pub c_cflag: tcflag_t,  // control modes
+        pub c_lflag: tcflag_t,  // local modes
+        pub c_line: cc_t,       // line discipline
+        pub c_cc: [cc_t; NCCS], // special characters
+        #[cfg(not(target_os = "android"))]
+        pub c_ispeed: speed_t, // input speed
+        #[cfg(not(target_os = "android"))]
+        pub c_ospeed: speed_t, // output speed
+    }
+
+    // c_iflag
+    pub const IGNBRK: tcflag_t = 0o000001;
+    pub const BRKINT: tcflag_t = 0o000002;
+    pub const PARMRK: tcflag_t = 0o000010;
+    pub const ISTRIP: tcflag_t = 0o000040;
+    pub const INLCR: tcflag_t = 0o000100;
+    pub const IGNCR: tcflag_t = 0o000200;
+    pub const ICRNL: tcflag_t = 0o000400;
+    pub const IXON: tcflag_t = 0o002000;
+
+    // c_oflag
+    pub const OPOST: tcflag_t = 0o000001;
+
+    // c_cflag
+    pub const CSIZE: tcflag_t = 0o000060;
+    pub const CS8: tcflag_t = 0o000060;
+    pub const PARENB: tcflag_t = 0o000400;
+
+    // c_lflag
+    pub const ISIG: tcflag_t = 0o000001;
+    pub const ICANON: tcflag_t = 0o000002;
+    pub const ECHO: tcflag_t = 0o000010;
+    pub const ECHONL: tcflag_t = 0o000100;
+    pub const IEXTEN: tcflag_t = 0o100000;
+
+    // c_cc indices
+    pub const VTIME: usize = 5;
+    pub const VMIN: usize = 6;
+
+    pub const TIOCGWINSZ: ioctl_request_t = 0x5413;
+    pub const TIOCSWINSZ: ioctl_request_t = 0x5414;
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+mod os {
+    use super::*;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub type tcflag_t = c_ulong;
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    pub type tcflag_t = c_uint;
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub type speed_t = c_ulong;
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    pub type speed_t = c_uint;
+    pub type ioctl_request_t = c_ulong;
+    pub type nfds_t = c_uint;
+
+    pub const NCCS: usize = 20;
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Termios {
+        pub c_iflag: tcflag_t,  // input modes
+        pub c_oflag: tcflag_t,  // output modes
+        pub c_cflag: tcflag_t,  // control modes
+        pub c_lflag: tcflag_t,  // local modes
+        pub c_cc: [cc_t; NCCS], // special characters
+        pub c_ispeed: speed_t,  // input speed
+        pub c_ospeed: speed_t,  // output speed
+    }
+
+    // c_iflag
+    pub const IGNBRK: tcflag_t = 0x00000001;
+    pub const BRKINT: tcflag_t = 0x00000002;
+    pub const PARMRK: tcflag_t = 0x00000008;
+    pub const ISTRIP: tcflag_t = 0x00000020;
+    pub const INLCR: tcflag_t = 0x00000040;
+    pub const IGNCR: tcflag_t = 0x00000080;
+    pub const ICRNL: tcflag_t = 0x00000100;
+    pub const IXON: tcflag_t = 0x00000200;
+
+    // c_oflag
+    pub const OPOST: tcflag_t = 0x00000001;
+
+    // c_cflag
+    pub const CSIZE: tcflag_t = 0x00000300;
+    pub const CS8: tcflag_t = 0x00000300;
+    pub const PARENB: tcflag_t = 0x00001000;
+
+    // c_lflag
+    pub const ECHO: tcflag_t = 0x00000008;
+    pub const ECHONL: tcflag_t = 0x00000010;
+    pub const ISIG: tcflag_t = 0x00000080;
+    pub const ICANON: tcflag_t = 0x00000100;
+    pub const IEXTEN: tcflag_t = 0x00000400;
+
+    // c_cc indices
+    pub const VMIN: usize = 16;
+    pub const VTIME: usize = 17;
+
+    pub const TIOCGWINSZ: ioctl_request_t = 0x40087468;
+    pub const TIOCSWINSZ: ioctl_request_t = 0x80087467;
+}
+
+pub use os::*;
+
+pub const TCSANOW: c_int = 0;
+pub const TCSAFLUSH: c_int = 2;
+
+pub const POLLIN: c_short = 0x1;
+pub const POLLOUT: c_short = 0x4;
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Winsize {
+    pub ws_row: u16,
+    pub ws_col: u16,
+    pub ws_xpixel: u16,
+    pub ws_ypixel: u16,
+}
+
+#[repr(C)]
+pub struct PollFd {
+    pub fd: c_int,
+    pub events: c_short,
+    pub revents: c_short,
+}
+
+mod sys {
+    use super::*;
+
+    extern "C" {
+        pub fn ioctl(fd: c_int, request: ioctl_request_t, ...) -> c_int;
+        pub fn tcgetattr(fd: c_int, termios_p: *mut Termios) -> c_int;
+        pub fn tcsetattr(fd: c_int, optional_actions: c_int, termios_p: *const Termios) -> c_int;
+        pub fn poll(fds: *mut PollFd, nfds: nfds_t, timeout: c_int) -> c_int;
+        pub fn read(fd: c_int, buf: *mut c_void, count: usize) -> isize;
+        pub fn write(fd: c_int, buf: *const c_void, count: usize) -> isize;
+    }
+
+    #[cfg_attr(
+        any(
+            target_os = "linux",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ),
+        link(name = "util")
+    )]
+    extern "C" {
+        pub fn openpty(
+            amaster: *mut c_int,
+            aslave: *mut c_int,
+            name: *mut u8,
+            termp: *const Termios,
+            winp: *const Winsize,
+        ) -> c_int;
+    }
+}
+
+fn check(res: c_int) -> io::Result<c_int> {
+    if res == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(res)
+    }
+}
+
+pub fn tcgetattr(fd: RawFd) -> io::Result<Termios> {
+    let mut termios = std::mem::MaybeUninit::<Termios>::uninit();
+    unsafe {
+        check(sys::tcgetattr(fd, termios.as_mut_ptr()))?;
+        Ok(termios.assume_init())
+    }
+}
+
+pub fn tcsetattr(fd: RawFd, optional_actions: c_int, termios: &Termios) -> io::Result<()> {
+    unsafe { check(sys::tcsetattr(fd, optional_actions, termios)).map(|_| ()) }
+}
+
+pub fn get_winsize(fd: RawFd) -> io::Result<Winsize> {
+    let mut winsize = Winsize::default();
+    unsafe { check(sys::ioctl(fd, TIOCGWINSZ, &mut winsize as *mut Winsize)) }?;
+    Ok(winsize)
+}
+
+pub fn set_winsize(fd: RawFd, winsize: &Winsize) -> io::Result<()> {
+    unsafe { check(sys::ioctl(fd, TIOCSWINSZ, winsize as *const Winsize)) }.map(|_| ())
+}
+
+/// Waits for events on the given file descriptors and returns the number of ready descriptors.
+///
+/// A negative timeout waits indefinitely.
+pub fn poll(fds: &mut [PollFd], timeout_ms: c_int) -> io::Result<usize> {
+    unsafe { check(sys::poll(fds.as_mut_ptr(), fds.len() as nfds_t, timeout_ms)) }
+        .map(|count| count as usize)
+}
+
+pub fn read(fd: RawFd, buffer: &mut [u8]) -> io::Result<usize> {
+    let count = unsafe { sys::read(fd, buffer.as_mut_ptr() as *mut c_void, buffer.len()) };
+    if count == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(count as usize)
+    }
+}
+
+pub fn write(fd: RawFd, buffer: &[u8]) -> io::Result<usize> {
+    let count = unsafe { sys::write(fd, buffer.as_ptr() as *const c_void, buffer.len()) };
+    if count == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(count as usize)
+    }
+}
+
+/// Opens a new pseudo-terminal and returns its master and slave file descriptors.
+pub fn openpty(winsize: Option<&Winsize>) -> io::Result<(OwnedFd, OwnedFd)> {
+    let mut master: c_int = -1;
+    let mut slave: c_int = -1;
+    let winp = winsize.map_or(std::ptr::null(), |winsize| winsize as *const Winsize);
+    unsafe {
+        check(sys::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            winp,
+        ))?;
+        Ok((OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::io::AsRawFd;
+
+    #[test]
+    fn tcgetattr_does_not_overflow() {
+        #[repr(C)]
+        struct Guarded {
+            termios: std::mem::MaybeUninit<Termios>,
+            canary: [u8; 64],
+        }
+
+        let (_master, slave) = openpty(None).unwrap();
+        let mut guarded = Guarded {
+            termios: std::mem::MaybeUninit::uninit(),
+            canary: [0xa5; 64],
+        };
+        let res = unsafe { sys::tcgetattr(slave.as_raw_fd(), guarded.termios.as_mut_ptr()) };
+        assert_eq!(res, 0);
+        assert_eq!(guarded.canary, [0xa5; 64]);
+    }
+
+    #[test]
+    fn termios_round_trip() {
+        let (_master, slave) = openpty(None).unwrap();
+        let original = tcgetattr(slave.as_raw_fd()).unwrap();
+
+        let mut termios = original;
+        termios.c_iflag &= !(ICRNL | IXON);
+        termios.c_lflag &= !(ECHO | ICANON | ISIG);
+        termios.c_cc[VMIN] = 3;
+        termios.c_cc[VTIME] = 7;
+        tcsetattr(slave.as_raw_fd(), TCSANOW, &termios).unwrap();
+        let modified = tcgetattr(slave.as_raw_fd()).unwrap();
+        assert_eq!(modified, termios);
+        assert_eq!(modified.c_lflag & (ECHO | ICANON | ISIG), 0);
+        assert_eq!(modified.c_cc[VMIN], 3);
+        assert_eq!(modified.c_cc[VTIME], 7);
+
+        tcsetattr(slave.as_raw_fd(), TCSANOW, &original).unwrap();
+        assert_eq!(tcgetattr(slave.as_raw_fd()).unwrap(), original);
+    }
+
+    #[test]
+    fn winsize_round_trip() {
+        let winsize = Winsize {
+            ws_row: 24,
+            ws_col: 80,
+            ..Default::default()
+        };
+        let (master, slave) = openpty(Some(&winsize)).unwrap();
+        assert_eq!(get_winsize(slave.as_raw_fd()).unwrap(), winsize);
+
+        let winsize = Winsize {
+            ws_row: 100,
+            ws_col: 300,
+            ..Default::default()
+        };
+        set_winsize(master.as_raw_fd(), &winsize).unwrap();
+        assert_eq!(get_winsize(slave.as_raw_fd()).unwrap(), winsize);
+    }
+
+    #[test]
+    fn poll_and_read() {
+        use std::io::Write;
+
+        let (master, slave) = openpty(None).unwrap();
+        let mut master = std::fs::File::from(master);
+        let mut termios = tcgetattr(slave.as_raw_fd()).unwrap();
+        termios.c_lflag &= !(ECHO | ICANON);
+        tcsetattr(slave.as_raw_fd(), TCSANOW, &termios).unwrap();
+
+        let mut poll_fd = PollFd {
+            fd: slave.as_raw_fd(),
+            events: POLLIN,
+            revents: 0,
+        };
+        assert_eq!(poll(std::slice::from_mut(&mut poll_fd), 0).unwrap(), 0);
+
+        master.write_all(b"wasd").unwrap();
+        assert_eq!(poll(std::slice::from_mut(&mut poll_fd), 1000).unwrap(), 1);
+        let mut buffer = [0u8; 16];
+        let count = read(slave.as_raw_fd(), &mut buffer).unwrap();
+        assert_eq!(&buffer[..count], b"wasd");
+    }
+
+    #[test]
+    fn poll_and_write() {
+        let (master, slave) = openpty(None).unwrap();
+        let mut poll_fd = PollFd {
+            fd: slave.as_raw_fd(),
+            events: POLLOUT,
+            revents: 0,
+        };
+        assert_eq!(poll(std::slice::from_mut(&mut poll_fd), 1000).unwrap(), 1);
+        assert_eq!(write(slave.as_raw_fd(), b"wasd").unwrap(), 4);
+        let mut buffer = [0u8; 16];
+        let count = read(master.as_raw_fd(), &mut buffer).unwrap();
+        assert_eq!(&buffer[..count], b"wasd");
+    }
+
+    #[test]
+    fn not_a_terminal() {
+        let file = std::fs::File::open("/dev/null").unwrap();
+        assert!(tcgetattr(file.as_raw_fd()).is_err());
+        assert!(get_winsize(file.as_raw_fd()).is_err());
+    }
+}
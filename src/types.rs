@@ -1,4 +1,4 @@
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct Vec2<T> {
     pub x: T,
     pub y: T,
@@ -6,6 +6,243 @@ pub struct Vec2<T> {
 
 pub type Dimensions = Vec2<usize>;
 pub type Position = Vec2<usize>;
+/// Signed vector, e.g. an offset between two positions.
+pub type IVec2 = Vec2<isize>;
+
+impl<T> Vec2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T: std::ops::Add<Output = T>> std::ops::Add for Vec2<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: std::ops::Sub<Output = T>> std::ops::Sub for Vec2<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: std::ops::Neg<Output = T>> std::ops::Neg for Vec2<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+/// Scales both components.
+impl<T: std::ops::Mul<Output = T> + Copy> std::ops::Mul<T> for Vec2<T> {
+    type Output = Self;
+
+    fn mul(self, factor: T) -> Self {
+        Self::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl<T: std::ops::AddAssign> std::ops::AddAssign for Vec2<T> {
+    fn add_assign(&mut self, other: Self) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl<T: std::ops::SubAssign> std::ops::SubAssign for Vec2<T> {
+    fn sub_assign(&mut self, other: Self) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+macro_rules! impl_distances {
+    ($($t:ty),*) => {
+        $(
+            impl Vec2<$t> {
+                /// Gets the number of orthogonal steps between two positions.
+                pub fn manhattan_distance(&self, other: &Self) -> usize {
+                    self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+                }
+
+                /// Gets the number of steps between two positions if diagonal steps are allowed.
+                pub fn chebyshev_distance(&self, other: &Self) -> usize {
+                    std::cmp::max(self.x.abs_diff(other.x), self.y.abs_diff(other.y))
+                }
+            }
+        )*
+    };
+}
+
+impl_distances!(usize, isize);
+
+impl Position {
+    /// Converts the position into a signed vector, or `None` if a component does not fit.
+    pub fn to_signed(&self) -> Option<IVec2> {
+        Some(IVec2::new(
+            isize::try_from(self.x).ok()?,
+            isize::try_from(self.y).ok()?,
+        ))
+    }
+
+    /// Moves the position by `offset`, or returns `None` if a component would become negative or
+    /// overflow.
+    pub fn checked_add_signed(&self, offset: IVec2) -> Option<Position> {
+        Some(Position::new(
+            self.x.checked_add_signed(offset.x)?,
+            self.y.checked_add_signed(offset.y)?,
+        ))
+    }
+
+    /// Moves the position by `offset`, or returns `None` if it would leave `bounds`.
+    pub fn checked_offset(&self, offset: IVec2, bounds: &Dimensions) -> Option<Position> {
+        self.checked_add_signed(offset)
+            .filter(|position| position.x < bounds.x && position.y < bounds.y)
+    }
+
+    /// Moves the position by `offset`, wrapping around at the edges of `bounds`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bounds` is empty.
+    pub fn wrapping_offset(&self, offset: IVec2, bounds: &Dimensions) -> Position {
+        let wrap = |value: usize, offset: isize, bound: usize| {
+            let offset = offset.rem_euclid(bound as isize) as usize;
+            (value % bound + offset) % bound
+        };
+        Position::new(
+            wrap(self.x, offset.x, bounds.x),
+            wrap(self.y, offset.y, bounds.y),
+        )
+    }
+}
+
+impl TryFrom<IVec2> for Position {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(vector: IVec2) -> Result<Self, Self::Error> {
+        Ok(Position::new(vector.x.try_into()?, vector.y.try_into()?))
+    }
+}
+
+/// One of the four orthogonal directions on the grid, where y grows downwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    pub fn is_vertical(&self) -> bool {
+        *self == Self::Up || *self == Self::Down
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    pub fn is_opposite(&self, other: Direction) -> bool {
+        self.opposite() == other
+    }
+
+    /// Gets the offset of a single step in this direction.
+    pub fn offset(&self) -> IVec2 {
+        match self {
+            Direction::Up => IVec2::new(0, -1),
+            Direction::Down => IVec2::new(0, 1),
+            Direction::Left => IVec2::new(-1, 0),
+            Direction::Right => IVec2::new(1, 0),
+        }
+    }
+}
+
+impl From<Direction> for IVec2 {
+    fn from(direction: Direction) -> Self {
+        direction.offset()
+    }
+}
+
+/// Axis-aligned rectangle of grid cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rect {
+    /// Top left cell.
+    pub origin: Position,
+    pub dimensions: Dimensions,
+}
+
+impl Rect {
+    pub const fn new(origin: Position, dimensions: Dimensions) -> Self {
+        Self { origin, dimensions }
+    }
+
+    /// Gets the x coordinate just beyond the right edge.
+    pub fn right(&self) -> usize {
+        self.origin.x + self.dimensions.x
+    }
+
+    /// Gets the y coordinate just beyond the bottom edge.
+    pub fn bottom(&self) -> usize {
+        self.origin.y + self.dimensions.y
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dimensions.x == 0 || self.dimensions.y == 0
+    }
+
+    pub fn area(&self) -> usize {
+        self.dimensions.x * self.dimensions.y
+    }
+
+    pub fn contains(&self, position: &Position) -> bool {
+        (self.origin.x..self.right()).contains(&position.x)
+            && (self.origin.y..self.bottom()).contains(&position.y)
+    }
+
+    /// Gets the overlapping part of two rectangles, or `None` if they do not overlap.
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let origin = Position::new(
+            std::cmp::max(self.origin.x, other.origin.x),
+            std::cmp::max(self.origin.y, other.origin.y),
+        );
+        let right = std::cmp::min(self.right(), other.right());
+        let bottom = std::cmp::min(self.bottom(), other.bottom());
+        let rect = Rect::new(
+            origin,
+            Dimensions::new(
+                right.saturating_sub(origin.x),
+                bottom.saturating_sub(origin.y),
+            ),
+        );
+        (!rect.is_empty()).then_some(rect)
+    }
+
+    /// Gets the cells of the rectangle row by row.
+    pub fn iter(&self) -> impl Iterator<Item = Position> {
+        let rect = *self;
+        (rect.origin.y..rect.bottom())
+            .flat_map(move |y| (rect.origin.x..rect.right()).map(move |x| Position::new(x, y)))
+    }
+}
 
 pub struct Matrix2<T: Default + Clone> {
     dimensions: Dimensions,
@@ -15,26 +252,450 @@ pub struct Matrix2<T: Default + Clone> {
 impl<T: Default + Clone> Matrix2<T> {
     pub fn new(dimensions: &Dimensions) -> Self {
         Self {
-            dimensions: dimensions.clone(),
+            dimensions: *dimensions,
             values: vec![T::default(); dimensions.x * dimensions.y],
         }
     }
 
+    pub fn dimensions(&self) -> &Dimensions {
+        &self.dimensions
+    }
+
+    /// Gets the rectangle covering all cells.
+    pub fn bounds(&self) -> Rect {
+        Rect::new(Position::default(), self.dimensions)
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.dimensions.x && y < self.dimensions.y
+    }
+
+    fn index_of(&self, x: usize, y: usize) -> Option<usize> {
+        self.contains(x, y).then(|| y * self.dimensions.x + x)
+    }
+
     pub fn get(&self, x: usize, y: usize) -> &T {
         &self.values[y * self.dimensions.x + x]
     }
 
+    /// Gets a cell, or `None` if it is out of bounds.
+    pub fn get_checked(&self, x: usize, y: usize) -> Option<&T> {
+        self.index_of(x, y).map(|index| &self.values[index])
+    }
+
+    /// Gets a cell for modification, or `None` if it is out of bounds.
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        self.index_of(x, y).map(|index| &mut self.values[index])
+    }
+
     pub fn set(&mut self, x: usize, y: usize, value: T) {
         self.values[y * self.dimensions.x + x] = value;
     }
 
+    /// Gets the cells row by row.
     pub fn iter(&self) -> std::slice::Iter<'_, T> {
         self.values.iter()
     }
 
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.values.iter_mut()
+    }
+
+    /// Gets the cells row by row together with their positions.
+    pub fn enumerate(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.bounds().iter().zip(self.values.iter())
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        let start = y * self.dimensions.x;
+        &self.values[start..start + self.dimensions.x]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        let start = y * self.dimensions.x;
+        &mut self.values[start..start + self.dimensions.x]
+    }
+
+    /// Gets the rows from top to bottom. A matrix without columns has no rows either.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks_exact` panics for zero-sized chunks, but then there are no values anyway.
+        self.values
+            .chunks_exact(std::cmp::max(self.dimensions.x, 1))
+            .take(self.dimensions.y)
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        self.values
+            .chunks_exact_mut(std::cmp::max(self.dimensions.x, 1))
+            .take(self.dimensions.y)
+    }
+
     pub fn clear(&mut self) {
         for value in &mut self.values {
             *value = T::default();
         }
     }
+
+    /// Sets all cells of a rectangle, clipped to the matrix.
+    pub fn fill_rect(&mut self, rect: &Rect, value: T) {
+        let Some(rect) = rect.intersect(&self.bounds()) else {
+            return;
+        };
+        for y in rect.origin.y..rect.bottom() {
+            self.row_mut(y)[rect.origin.x..rect.right()].fill(value.clone());
+        }
+    }
+
+    /// Copies another matrix into this one with its top left corner at `offset`.
+    ///
+    /// Cells falling outside of this matrix are clipped, so the offset may also be negative.
+    pub fn blit(&mut self, source: &Matrix2<T>, offset: IVec2) {
+        // Clip the source to the part that lands on this matrix, in source coordinates.
+        let visible = |offset: isize, source_len: usize, len: usize| {
+            let start = offset.min(0).unsigned_abs().min(source_len);
+            let end = (len as isize)
+                .saturating_sub(offset)
+                .clamp(0, source_len as isize) as usize;
+            start..std::cmp::max(start, end)
+        };
+        let columns = visible(offset.x, source.dimensions.x, self.dimensions.x);
+        if columns.is_empty() {
+            return;
+        }
+        for y in visible(offset.y, source.dimensions.y, self.dimensions.y) {
+            let target_y = y.wrapping_add_signed(offset.y);
+            let target_x = columns.start.wrapping_add_signed(offset.x);
+            self.row_mut(target_y)[target_x..target_x + columns.len()]
+                .clone_from_slice(&source.row(y)[columns.clone()]);
+        }
+    }
+
+    /// Changes the dimensions, keeping the cells that are within both the old and new bounds.
+    pub fn resize(&mut self, dimensions: &Dimensions) {
+        let mut resized = Matrix2::new(dimensions);
+        resized.blit(self, IVec2::default());
+        *self = resized;
+    }
+
+    /// Constructs a matrix of the same dimensions by applying `f` to every cell.
+    pub fn map<U: Default + Clone>(&self, f: impl FnMut(&T) -> U) -> Matrix2<U> {
+        Matrix2 {
+            dimensions: self.dimensions,
+            values: self.values.iter().map(f).collect(),
+        }
+    }
+
+    /// Gets the orthogonally adjacent positions within bounds.
+    pub fn neighbors4(&self, position: &Position) -> impl Iterator<Item = Position> {
+        let (position, dimensions) = (*position, self.dimensions);
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| position.checked_offset(direction.offset(), &dimensions))
+    }
+
+    /// Gets the orthogonally and diagonally adjacent positions within bounds.
+    pub fn neighbors8(&self, position: &Position) -> impl Iterator<Item = Position> {
+        const OFFSETS: [IVec2; 8] = [
+            IVec2::new(-1, -1),
+            IVec2::new(0, -1),
+            IVec2::new(1, -1),
+            IVec2::new(-1, 0),
+            IVec2::new(1, 0),
+            IVec2::new(-1, 1),
+            IVec2::new(0, 1),
+            IVec2::new(1, 1),
+        ];
+        let (position, dimensions) = (*position, self.dimensions);
+        OFFSETS
+            .into_iter()
+            .filter_map(move |offset| position.checked_offset(offset, &dimensions))
+    }
+}
+
+impl<T: Default + Clone> std::ops::Index<(usize, usize)> for Matrix2<T> {
+    type Output = T;
+
+    /// Gets the cell at `(x, y)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of bounds.
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        let dimensions = self.dimensions;
+        self.get_checked(x, y).unwrap_or_else(|| {
+            panic!(
+                "position ({}, {}) out of bounds for dimensions {}x{}",
+                x, y, dimensions.x, dimensions.y
+            )
+        })
+    }
+}
+
+impl<T: Default + Clone> std::ops::IndexMut<(usize, usize)> for Matrix2<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+        let dimensions = self.dimensions;
+        self.get_mut(x, y).unwrap_or_else(|| {
+            panic!(
+                "position ({}, {}) out of bounds for dimensions {}x{}",
+                x, y, dimensions.x, dimensions.y
+            )
+        })
+    }
+}
+
+impl<T: Default + Clone> std::ops::Index<Position> for Matrix2<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &Self::Output {
+        &self[(position.x, position.y)]
+    }
+}
+
+impl<T: Default + Clone> std::ops::IndexMut<Position> for Matrix2<T> {
+    fn index_mut(&mut self, position: Position) -> &mut Self::Output {
+        &mut self[(position.x, position.y)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = IVec2::new(1, -2);
+        let b = IVec2::new(3, 4);
+        assert_eq!(a + b, IVec2::new(4, 2));
+        assert_eq!(a - b, IVec2::new(-2, -6));
+        assert_eq!(-a, IVec2::new(-1, 2));
+        assert_eq!(a * 3, IVec2::new(3, -6));
+        let mut c = a;
+        c += b;
+        c -= IVec2::new(1, 1);
+        assert_eq!(c, IVec2::new(3, 1));
+        assert_eq!(
+            Position::new(1, 2) + Position::new(3, 4),
+            Position::new(4, 6)
+        );
+    }
+
+    #[test]
+    fn distances() {
+        let a = Position::new(1, 5);
+        let b = Position::new(4, 1);
+        assert_eq!(a.manhattan_distance(&b), 7);
+        assert_eq!(a.chebyshev_distance(&b), 4);
+        let a = IVec2::new(-3, 2);
+        assert_eq!(a.manhattan_distance(&IVec2::new(1, 1)), 5);
+        assert_eq!(a.chebyshev_distance(&a), 0);
+    }
+
+    #[test]
+    fn direction() {
+        for direction in Direction::ALL {
+            assert!(direction.is_opposite(direction.opposite()));
+            assert_eq!(
+                direction.offset() + direction.opposite().offset(),
+                IVec2::default()
+            );
+            assert_eq!(direction.offset().manhattan_distance(&IVec2::default()), 1);
+            assert_eq!(direction.is_vertical(), direction.offset().x == 0);
+        }
+        assert_eq!(IVec2::from(Direction::Up), IVec2::new(0, -1));
+        assert!(!Direction::Up.is_opposite(Direction::Left));
+    }
+
+    #[test]
+    fn checked_position_arithmetic() {
+        let bounds = Dimensions::new(4, 3);
+        let origin = Position::new(0, 0);
+        assert_eq!(origin.checked_add_signed(Direction::Up.offset()), None);
+        assert_eq!(
+            origin.checked_offset(Direction::Left.offset(), &bounds),
+            None
+        );
+        assert_eq!(
+            origin.checked_offset(Direction::Down.offset(), &bounds),
+            Some(Position::new(0, 1))
+        );
+        let corner = Position::new(3, 2);
+        assert_eq!(
+            corner.checked_offset(Direction::Right.offset(), &bounds),
+            None
+        );
+        assert_eq!(
+            corner.checked_offset(Direction::Down.offset(), &bounds),
+            None
+        );
+        assert_eq!(
+            Position::new(usize::MAX, 0).checked_add_signed(IVec2::new(1, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn wrapping_position_arithmetic() {
+        let bounds = Dimensions::new(4, 3);
+        let origin = Position::new(0, 0);
+        assert_eq!(
+            origin.wrapping_offset(Direction::Up.offset(), &bounds),
+            Position::new(0, 2)
+        );
+        assert_eq!(
+            origin.wrapping_offset(Direction::Left.offset(), &bounds),
+            Position::new(3, 0)
+        );
+        assert_eq!(
+            Position::new(3, 2).wrapping_offset(IVec2::new(9, -7), &bounds),
+            Position::new(0, 1)
+        );
+    }
+
+    #[test]
+    fn signed_conversion() {
+        let position = Position::new(3, 4);
+        assert_eq!(position.to_signed(), Some(IVec2::new(3, 4)));
+        assert_eq!(Position::try_from(IVec2::new(3, 4)), Ok(position));
+        assert!(Position::try_from(IVec2::new(-1, 4)).is_err());
+        assert_eq!(Position::new(usize::MAX, 0).to_signed(), None);
+    }
+
+    #[test]
+    fn rect() {
+        let rect = Rect::new(Position::new(1, 2), Dimensions::new(3, 2));
+        assert_eq!((rect.right(), rect.bottom(), rect.area()), (4, 4, 6));
+        assert!(rect.contains(&Position::new(1, 2)));
+        assert!(rect.contains(&Position::new(3, 3)));
+        assert!(!rect.contains(&Position::new(4, 3)));
+        assert!(!rect.contains(&Position::new(0, 2)));
+        assert_eq!(
+            rect.iter().collect::<Vec<_>>(),
+            [(1, 2), (2, 2), (3, 2), (1, 3), (2, 3), (3, 3)].map(|(x, y)| Position::new(x, y))
+        );
+        assert!(rect.iter().all(|position| rect.contains(&position)));
+
+        let other = Rect::new(Position::new(3, 0), Dimensions::new(5, 3));
+        assert_eq!(
+            rect.intersect(&other),
+            Some(Rect::new(Position::new(3, 2), Dimensions::new(1, 1)))
+        );
+        assert_eq!(other.intersect(&rect), rect.intersect(&other));
+        let apart = Rect::new(Position::new(4, 2), Dimensions::new(1, 1));
+        assert_eq!(rect.intersect(&apart), None);
+        assert!(Rect::default().is_empty());
+        assert_eq!(Rect::default().iter().count(), 0);
+    }
+
+    /// Constructs a matrix whose cells hold their row-major index.
+    fn numbered(dimensions: Dimensions) -> Matrix2<usize> {
+        let mut matrix = Matrix2::new(&dimensions);
+        matrix
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, value)| *value = i);
+        matrix
+    }
+
+    #[test]
+    fn matrix_access() {
+        let mut matrix = numbered(Dimensions::new(3, 2));
+        assert_eq!(matrix[(2, 1)], 5);
+        assert_eq!(matrix[Position::new(1, 0)], 1);
+        assert_eq!(matrix.get_checked(2, 1), Some(&5));
+        assert_eq!(matrix.get_checked(3, 0), None);
+        assert_eq!(matrix.get_checked(0, 2), None);
+        *matrix.get_mut(0, 1).unwrap() = 9;
+        matrix[(1, 1)] = 8;
+        assert_eq!(matrix.row(1), [9, 8, 5]);
+        assert_eq!(matrix.get_mut(3, 1), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn matrix_index_out_of_bounds() {
+        let matrix = numbered(Dimensions::new(3, 2));
+        let _ = matrix[(3, 0)];
+    }
+
+    #[test]
+    fn matrix_rows_and_enumerate() {
+        let mut matrix = numbered(Dimensions::new(3, 2));
+        assert_eq!(
+            matrix.rows().collect::<Vec<_>>(),
+            [&[0, 1, 2][..], &[3, 4, 5][..]]
+        );
+        matrix.rows_mut().for_each(|row| row.reverse());
+        assert_eq!(matrix.row(0), [2, 1, 0]);
+        assert!(matrix
+            .enumerate()
+            .all(|(position, value)| *value == position.y * 3 + 2 - position.x));
+        let mut empty = Matrix2::<u8>::new(&Dimensions::new(0, 2));
+        assert_eq!(empty.rows().count(), 0);
+        assert_eq!(empty.rows_mut().count(), 0);
+    }
+
+    #[test]
+    fn matrix_fill_rect() {
+        let mut matrix = Matrix2::<u8>::new(&Dimensions::new(4, 3));
+        matrix.fill_rect(&Rect::new(Position::new(2, 1), Dimensions::new(5, 5)), 1);
+        assert_eq!(
+            matrix.rows().collect::<Vec<_>>(),
+            [&[0, 0, 0, 0][..], &[0, 0, 1, 1][..], &[0, 0, 1, 1][..]]
+        );
+        matrix.fill_rect(&Rect::new(Position::new(9, 9), Dimensions::new(1, 1)), 2);
+        assert!(matrix.iter().all(|value| *value < 2));
+    }
+
+    #[test]
+    fn matrix_blit_clips() {
+        let source = numbered(Dimensions::new(2, 2)).map(|value| *value + 1);
+        let mut matrix = Matrix2::<usize>::new(&Dimensions::new(3, 3));
+        matrix.blit(&source, IVec2::new(2, -1));
+        assert_eq!(
+            matrix.rows().collect::<Vec<_>>(),
+            [&[0, 0, 3][..], &[0, 0, 0][..], &[0, 0, 0][..]]
+        );
+        matrix.blit(&source, IVec2::new(-1, 2));
+        assert_eq!(matrix.row(2), [2, 0, 0]);
+        matrix.blit(&source, IVec2::new(1, 1));
+        assert_eq!(
+            matrix.rows().collect::<Vec<_>>(),
+            [&[0, 0, 3][..], &[0, 1, 2][..], &[2, 3, 4][..]]
+        );
+        let unchanged = matrix.map(|value| *value);
+        for offset in [(3, 0), (0, 3), (-2, 0), (0, -2), (isize::MAX, isize::MIN)] {
+            matrix.blit(&source, IVec2::new(offset.0, offset.1));
+        }
+        assert!(matrix.iter().eq(unchanged.iter()));
+    }
+
+    #[test]
+    fn matrix_resize() {
+        let mut matrix = numbered(Dimensions::new(3, 2));
+        matrix.resize(&Dimensions::new(2, 3));
+        assert_eq!(matrix.dimensions(), &Dimensions::new(2, 3));
+        assert_eq!(
+            matrix.rows().collect::<Vec<_>>(),
+            [&[0, 1][..], &[3, 4][..], &[0, 0][..]]
+        );
+    }
+
+    #[test]
+    fn matrix_neighbors() {
+        let matrix = Matrix2::<u8>::new(&Dimensions::new(3, 3));
+        let mut neighbors: Vec<Position> = matrix.neighbors4(&Position::new(0, 1)).collect();
+        neighbors.sort_by_key(|position| (position.y, position.x));
+        assert_eq!(
+            neighbors,
+            [
+                Position::new(0, 0),
+                Position::new(1, 1),
+                Position::new(0, 2)
+            ]
+        );
+        assert_eq!(matrix.neighbors4(&Position::new(1, 1)).count(), 4);
+        assert_eq!(matrix.neighbors8(&Position::new(1, 1)).count(), 8);
+        assert_eq!(matrix.neighbors8(&Position::new(2, 2)).count(), 3);
+        assert_eq!(matrix.neighbors8(&Position::new(1, 0)).count(), 5);
+    }
 }
@@ -0,0 +1,91 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use common::VirtualScreen;
+use rustsnake::asciicast::Recorder;
+use rustsnake::frame_buffer::{Color, FrameBuffer, Pixel};
+use rustsnake::types::{Dimensions, Position};
+use std::time::Duration;
+
+const WIDTH: usize = 10;
+const HEIGHT: usize = 3;
+
+/// Decodes a JSON string starting at the opening quote and returns it with the rest of the input.
+fn json_string(input: &str) -> (String, &str) {
+    let mut chars = input.strip_prefix('"').unwrap().char_indices();
+    let mut text = String::new();
+    while let Some((i, character)) = chars.next() {
+        match character {
+            '"' => return (text, &input[i + 2..]),
+            '\\' => match chars.next().unwrap().1 {
+                'n' => text.push('\n'),
+                'r' => text.push('\r'),
+                't' => text.push('\t'),
+                'u' => {
+                    let hex: String = (0..4).map(|_| chars.next().unwrap().1).collect();
+                    text.push(char::from_u32(u32::from_str_radix(&hex, 16).unwrap()).unwrap());
+                }
+                escaped => text.push(escaped),
+            },
+            character => text.push(character),
+        }
+    }
+    panic!("unterminated string: {}", input);
+}
+
+/// Parses the output events of a recording into their times and data.
+fn events(recording: &str) -> Vec<(f64, String)> {
+    recording
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let (time, rest) = line
+                .strip_prefix('[')
+                .unwrap()
+                .split_once(", \"o\", ")
+                .unwrap();
+            let (data, rest) = json_string(rest);
+            assert_eq!(rest, "]");
+            (time.parse().unwrap(), data)
+        })
+        .collect()
+}
+
+#[test]
+fn record_offline() {
+    let dimensions = Dimensions::new(WIDTH, HEIGHT);
+    let mut frame_buffer = FrameBuffer::new(&dimensions);
+    let mut recorder = Recorder::new(Vec::new(), &dimensions, None).unwrap();
+    // Frames are rendered as fast as possible with the times they would be shown at.
+    let step = Duration::from_millis(100);
+    for tick in 0..WIDTH * 2 {
+        let x = tick % WIDTH;
+        frame_buffer
+            .canvas()
+            .print(Position::new(0, 0), "\"snake\"\\", Color::Red);
+        frame_buffer
+            .back_buffer()
+            .set_pixel(&Position::new(x, 2), Pixel::new('◉', Color::Green));
+        frame_buffer.swap_buffers_to(&mut std::io::sink()).unwrap();
+        recorder
+            .output(step * tick as u32, frame_buffer.last_output())
+            .unwrap();
+    }
+    let recording = String::from_utf8(recorder.finish().unwrap()).unwrap();
+    assert_eq!(
+        recording.lines().next(),
+        Some("{\"version\": 2, \"width\": 10, \"height\": 3}")
+    );
+
+    let events = events(&recording);
+    assert_eq!(events.len(), WIDTH * 2);
+    assert_eq!(events[0].0, 0.0);
+    assert_eq!(events.last().unwrap().0, 1.9);
+    let mut screen = VirtualScreen::new(WIDTH, HEIGHT);
+    for (_, data) in &events {
+        screen.feed(data.as_bytes());
+    }
+    assert_eq!(screen.row(0), "\"snake\"\\");
+    assert_eq!(screen.row(2), "         ◉");
+}
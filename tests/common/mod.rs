@@ -0,0 +1,448 @@
+#![allow(dead_code)]
+
+use rustsnake::frame_buffer::width::{char_width, is_mark};
+use rustsnake::frame_buffer::Marks;
+use rustsnake::terminal::ffi;
+use std::io::Write;
+use std::os::unix::io::{AsRawFd, OwnedFd};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Placeholder in the cell covered by the right half of a wide character.
+pub const CONTINUATION: char = '\0';
+
+/// Character, combining marks and foreground color (SGR parameter, 0 for the default) of a cell.
+#[derive(Clone, Copy, PartialEq)]
+struct Cell {
+    character: char,
+    marks: Marks,
+    color: u8,
+}
+
+const BLANK: Cell = Cell {
+    character: ' ',
+    marks: Marks::NONE,
+    color: 0,
+};
+
+/// Minimal terminal emulator that interprets the escape sequences emitted by the game.
+#[derive(Clone)]
+pub struct VirtualScreen {
+    width: usize,
+    height: usize,
+    main: Vec<Vec<Cell>>,
+    alternate: Vec<Vec<Cell>>,
+    alternate_active: bool,
+    cursor: (usize, usize),
+    saved_cursor: (usize, usize),
+    pub cursor_visible: bool,
+    /// Most recently written character, repeated by `REP`.
+    last_character: Option<char>,
+    /// Foreground color of written characters.
+    color: u8,
+    /// Whether a synchronized update (mode 2026) is in progress.
+    pub synchronized_update: bool,
+}
+
+impl VirtualScreen {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            main: vec![vec![BLANK; width]; height],
+            alternate: vec![vec![BLANK; width]; height],
+            alternate_active: false,
+            cursor: (0, 0),
+            saved_cursor: (0, 0),
+            cursor_visible: true,
+            last_character: None,
+            color: 0,
+            synchronized_update: false,
+        }
+    }
+
+    pub fn alternate_active(&self) -> bool {
+        self.alternate_active
+    }
+
+    fn lines_mut(&mut self) -> &mut Vec<Vec<Cell>> {
+        match self.alternate_active {
+            true => &mut self.alternate,
+            false => &mut self.main,
+        }
+    }
+
+    fn lines(&self) -> &Vec<Vec<Cell>> {
+        match self.alternate_active {
+            true => &self.alternate,
+            false => &self.main,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> char {
+        self.lines()[y][x].character
+    }
+
+    /// Gets the SGR parameter of the foreground color of a cell, 0 for the default color.
+    pub fn color(&self, x: usize, y: usize) -> u8 {
+        self.lines()[y][x].color
+    }
+
+    /// Gets a row of the visible screen with trailing spaces removed.
+    pub fn row(&self, y: usize) -> String {
+        self.lines()[y]
+            .iter()
+            .filter(|cell| cell.character != CONTINUATION)
+            .flat_map(|cell| std::iter::once(cell.character).chain(cell.marks.iter()))
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    pub fn contains(&self, text: &str) -> bool {
+        (0..self.height).any(|y| self.row(y).contains(text))
+    }
+
+    /// Finds the first occurrence of a character on the visible screen.
+    pub fn find(&self, character: char) -> Option<(usize, usize)> {
+        (0..self.height).find_map(|y| {
+            self.lines()[y]
+                .iter()
+                .position(|cell| cell.character == character)
+                .map(|x| (x, y))
+        })
+    }
+
+    fn clear(&mut self) {
+        let (width, height) = (self.width, self.height);
+        *self.lines_mut() = vec![vec![BLANK; width]; height];
+    }
+
+    fn line_feed(&mut self) {
+        if self.cursor.1 + 1 < self.height {
+            self.cursor.1 += 1;
+        } else {
+            let width = self.width;
+            let lines = self.lines_mut();
+            lines.remove(0);
+            lines.push(vec![BLANK; width]);
+        }
+    }
+
+    /// Blanks the other half when half of a wide character is overwritten, like terminals do.
+    fn split_wide(&mut self, x: usize, y: usize) {
+        let width = self.width;
+        let line = &mut self.lines_mut()[y];
+        if line[x].character == CONTINUATION {
+            line[x - 1] = BLANK;
+        } else if x + 1 < width && line[x + 1].character == CONTINUATION {
+            line[x + 1] = BLANK;
+        }
+    }
+
+    /// Adds a combining mark to the cell left of the cursor.
+    fn attach_mark(&mut self, mark: char) {
+        let (x, y) = (self.cursor.0.min(self.width), self.cursor.1);
+        let Some(mut x) = x.checked_sub(1) else {
+            return;
+        };
+        let line = &mut self.lines_mut()[y];
+        if line[x].character == CONTINUATION && x > 0 {
+            x -= 1;
+        }
+        line[x].marks.push(mark);
+    }
+
+    fn put(&mut self, character: char) {
+        if is_mark(character) {
+            self.attach_mark(character);
+            return;
+        }
+        let width = char_width(character).max(1);
+        if self.cursor.0 + width > self.width {
+            self.cursor.0 = 0;
+            self.line_feed();
+        }
+        let (x, y) = self.cursor;
+        for column in x..x + width {
+            self.split_wide(column, y);
+        }
+        let color = self.color;
+        let line = &mut self.lines_mut()[y];
+        line[x] = Cell {
+            character,
+            marks: Marks::NONE,
+            color,
+        };
+        if width == 2 {
+            line[x + 1] = Cell {
+                character: CONTINUATION,
+                marks: Marks::NONE,
+                color,
+            };
+        }
+        self.cursor.0 += width;
+        self.last_character = Some(character);
+    }
+
+    fn csi(&mut self, private: bool, params: &str, intermediates: &str, command: char) {
+        let mut numbers = params.split(';').map(|x| x.parse::<usize>().ok());
+        let mut next = |default: usize| numbers.next().flatten().unwrap_or(default);
+        if !intermediates.is_empty() {
+            match (private, intermediates, command) {
+                // Mode requests are answered by the test, if at all.
+                (true, "$", 'p') => return,
+                _ => panic!(
+                    "unsupported escape sequence: {}{}{}",
+                    params, intermediates, command
+                ),
+            }
+        }
+        match (private, command) {
+            (false, 'H') => {
+                let y = next(1).max(1) - 1;
+                let x = next(1).max(1) - 1;
+                self.cursor = (x.min(self.width - 1), y.min(self.height - 1));
+            }
+            (false, 'A' | 'B' | 'C' | 'D') => {
+                let count = next(1).max(1);
+                // Moving ends a pending wrap.
+                let (x, y) = (self.cursor.0.min(self.width - 1), self.cursor.1);
+                self.cursor = match command {
+                    'A' => (x, y.saturating_sub(count)),
+                    'B' => (x, (y + count).min(self.height - 1)),
+                    'C' => ((x + count).min(self.width - 1), y),
+                    _ => (x.saturating_sub(count), y),
+                };
+            }
+            (false, 'X') => {
+                let (x, y) = (self.cursor.0.min(self.width - 1), self.cursor.1);
+                let end = (x + next(1).max(1)).min(self.width);
+                self.split_wide(x, y);
+                self.split_wide(end - 1, y);
+                self.lines_mut()[y][x..end].fill(BLANK);
+            }
+            (false, 'b') => {
+                let character = self
+                    .last_character
+                    .expect("REP without a preceding character");
+                for _ in 0..next(1).max(1) {
+                    self.put(character);
+                }
+            }
+            (false, 'J') if next(0) == 2 => self.clear(),
+            // Device attribute requests are answered by the test, if at all.
+            (false, 'c') => {}
+            (false, 'm') => {
+                for parameter in params.split(';') {
+                    self.color = match parameter.parse().unwrap_or(0) {
+                        0 | 39 => 0,
+                        color @ 30..=37 => color,
+                        _ => self.color,
+                    };
+                }
+            }
+            (true, 'h') | (true, 'l') => {
+                let enable = command == 'h';
+                for mode in params.split(';') {
+                    match mode {
+                        "25" => self.cursor_visible = enable,
+                        "2026" => self.synchronized_update = enable,
+                        "1049" if enable && !self.alternate_active => {
+                            self.saved_cursor = self.cursor;
+                            self.alternate_active = true;
+                            self.clear();
+                        }
+                        "1049" if !enable && self.alternate_active => {
+                            self.alternate_active = false;
+                            self.cursor = self.saved_cursor;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => panic!("unsupported escape sequence: {}{}", params, command),
+        }
+    }
+
+    /// Interprets a stream of bytes written to the terminal.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let text = String::from_utf8_lossy(bytes);
+        let mut chars = text.chars().peekable();
+        while let Some(character) = chars.next() {
+            match character {
+                '\x1b' => match chars.next() {
+                    Some('[') => {
+                        let private = chars.next_if_eq(&'?').is_some();
+                        let mut params = String::new();
+                        while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == ';') {
+                            params.push(c);
+                        }
+                        let mut intermediates = String::new();
+                        while let Some(c) = chars.next_if(|c| (' '..='/').contains(c)) {
+                            intermediates.push(c);
+                        }
+                        if let Some(command) = chars.next() {
+                            self.csi(private, &params, &intermediates, command);
+                        }
+                    }
+                    Some('c') => {
+                        self.clear();
+                        self.cursor = (0, 0);
+                    }
+                    other => panic!("unsupported escape sequence: {:?}", other),
+                },
+                '\r' => self.cursor.0 = 0,
+                '\n' => self.line_feed(),
+                character => self.put(character),
+            }
+        }
+    }
+}
+
+/// The game running on a pseudo-terminal.
+pub struct Session {
+    master: std::fs::File,
+    slave: OwnedFd,
+    child: std::process::Child,
+    initial_termios: ffi::Termios,
+    output: Vec<u8>,
+    width: usize,
+    height: usize,
+}
+
+impl Session {
+    pub fn spawn(width: usize, height: usize, args: &[&str]) -> Self {
+        Self::spawn_in(&std::env::current_dir().unwrap(), width, height, args)
+    }
+
+    /// Spawns the game in the given working directory.
+    pub fn spawn_in(directory: &Path, width: usize, height: usize, args: &[&str]) -> Self {
+        let winsize = ffi::Winsize {
+            ws_row: height as u16,
+            ws_col: width as u16,
+            ..Default::default()
+        };
+        let (master, slave) = ffi::openpty(Some(&winsize)).unwrap();
+        let initial_termios = ffi::tcgetattr(slave.as_raw_fd()).unwrap();
+        let child = std::process::Command::new(env!("CARGO_BIN_EXE_rustsnake"))
+            .args(args)
+            .current_dir(directory)
+            .stdin(slave.try_clone().unwrap())
+            .stdout(slave.try_clone().unwrap())
+            .stderr(slave.try_clone().unwrap())
+            .spawn()
+            .unwrap();
+        Self {
+            master: master.into(),
+            slave,
+            child,
+            initial_termios,
+            output: Vec::new(),
+            width,
+            height,
+        }
+    }
+
+    /// Gets the current attributes of the pseudo-terminal.
+    pub fn termios(&self) -> ffi::Termios {
+        ffi::tcgetattr(self.slave.as_raw_fd()).unwrap()
+    }
+
+    /// Gets the attributes the pseudo-terminal had before the game was started.
+    pub fn initial_termios(&self) -> ffi::Termios {
+        self.initial_termios
+    }
+
+    pub fn send(&mut self, bytes: &[u8]) {
+        self.master.write_all(bytes).unwrap();
+    }
+
+    /// Reads all output that arrives within the timeout.
+    fn pump(&mut self, timeout: Duration) -> bool {
+        let mut poll_fd = ffi::PollFd {
+            fd: self.master.as_raw_fd(),
+            events: ffi::POLLIN,
+            revents: 0,
+        };
+        let timeout_ms = timeout.as_millis() as i32;
+        if ffi::poll(std::slice::from_mut(&mut poll_fd), timeout_ms).unwrap() == 0 {
+            return false;
+        }
+        let mut buffer = [0u8; 4096];
+        match ffi::read(self.master.as_raw_fd(), &mut buffer) {
+            Ok(count) => {
+                self.output.extend_from_slice(&buffer[..count]);
+                count > 0
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    pub fn screen(&self) -> VirtualScreen {
+        let mut screen = VirtualScreen::new(self.width, self.height);
+        screen.feed(&self.output);
+        screen
+    }
+
+    /// Reads output until the screen satisfies the predicate or the timeout elapses.
+    pub fn wait_for(
+        &mut self,
+        timeout: Duration,
+        predicate: impl Fn(&VirtualScreen) -> bool,
+    ) -> Option<VirtualScreen> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let screen = self.screen();
+            if predicate(&screen) {
+                return Some(screen);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            self.pump(deadline - now);
+        }
+    }
+
+    /// Reads output until new output arrives or the timeout elapses.
+    pub fn wait_for_output(&mut self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let output_len = self.output.len();
+        while self.output.len() == output_len {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            self.pump(deadline - now);
+        }
+        true
+    }
+
+    /// Waits for the game to exit, reads the remaining output and returns the exit status.
+    pub fn wait(&mut self, timeout: Duration) -> std::process::ExitStatus {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(status) = self.child.try_wait().unwrap() {
+                while self.pump(Duration::from_millis(50)) {}
+                return status;
+            }
+            if Instant::now() >= deadline {
+                self.child.kill().unwrap();
+                panic!("the game did not exit in time");
+            }
+            self.pump(Duration::from_millis(10));
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,276 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use common::Session;
+use std::time::Duration;
+
+const WIDTH: usize = 40;
+const HEIGHT: usize = 12;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+const WALL: char = '█';
+const SNAKE: char = '◉';
+const FOOD: char = 'x';
+
+#[test]
+fn renders_border_and_status_line() {
+    let mut session = Session::spawn(WIDTH, HEIGHT, &[]);
+    let screen = session
+        .wait_for(TIMEOUT, |screen| screen.contains("Speed: 0"))
+        .expect("the status line was not rendered");
+
+    assert!(screen.alternate_active());
+    assert!(!screen.cursor_visible);
+    // The playfield covers all rows but the last one, which holds the status line.
+    for x in 0..WIDTH {
+        assert_eq!(screen.get(x, 0), WALL);
+        assert_eq!(screen.get(x, HEIGHT - 2), WALL);
+    }
+    for y in 0..HEIGHT - 1 {
+        assert_eq!(screen.get(0, y), WALL);
+        assert_eq!(screen.get(WIDTH - 1, y), WALL);
+    }
+    assert!(screen.row(HEIGHT - 1).starts_with(" Score: 0"));
+    assert!(screen.row(HEIGHT - 1).ends_with("Speed: 0"));
+    assert!(screen.find(SNAKE).is_some());
+    assert!(screen.find(FOOD).is_some());
+}
+
+#[test]
+fn quit_restores_terminal() {
+    let mut session = Session::spawn(WIDTH, HEIGHT, &[]);
+    let termios = session.initial_termios();
+    session
+        .wait_for(TIMEOUT, |screen| screen.contains("Score: 0"))
+        .expect("the game did not start");
+    assert_ne!(session.termios(), termios);
+
+    session.send(b"q");
+    assert!(session.wait(TIMEOUT).success());
+    assert_eq!(session.termios(), termios);
+
+    let screen = session.screen();
+    assert!(!screen.alternate_active());
+    assert!(screen.cursor_visible);
+    assert_eq!(screen.row(0), "Final score: 0");
+    assert!(screen.row(1).starts_with("Seed: "));
+    assert!(screen.find(WALL).is_none());
+}
+
+#[test]
+fn ctrl_c_quits() {
+    let mut session = Session::spawn(WIDTH, HEIGHT, &[]);
+    let termios = session.initial_termios();
+    session
+        .wait_for(TIMEOUT, |screen| screen.contains("Score: 0"))
+        .expect("the game did not start");
+
+    session.send(b"\x03");
+    assert!(session.wait(TIMEOUT).success());
+    assert_eq!(session.termios(), termios);
+}
+
+#[test]
+fn synchronized_output() {
+    let mut session = Session::spawn(WIDTH, HEIGHT, &[]);
+    while !session.output().ends_with(b"\x1b[?2026$p\x1b[c") {
+        assert!(
+            session.wait_for_output(TIMEOUT),
+            "mode 2026 was not requested"
+        );
+    }
+    session.send(b"\x1b[?2026;2$y\x1b[?62;22c");
+    let screen = session
+        .wait_for(TIMEOUT, |screen| screen.contains("Score: 0"))
+        .expect("the game did not start");
+    assert!(!screen.synchronized_update);
+    let output = String::from_utf8_lossy(session.output()).into_owned();
+    let frame = &output[output.find("\x1b[c").unwrap()..];
+    assert!(frame.contains("\x1b[?2026h"));
+    assert!(frame.contains("\x1b[?2026l"));
+}
+
+#[test]
+fn screenshot() {
+    let directory =
+        std::env::temp_dir().join(format!("rustsnake-screenshot-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let mut session = Session::spawn_in(&directory, WIDTH, HEIGHT, &[]);
+    session
+        .wait_for(TIMEOUT, |screen| screen.contains("Score: 0"))
+        .expect("the game did not start");
+    session.send(b"e");
+    session
+        .wait_for(TIMEOUT, |screen| screen.contains("Saved rustsnake-"))
+        .expect("the screenshot was not saved");
+    let mut files: Vec<_> = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    std::fs::remove_dir_all(&directory).unwrap();
+    let extensions: Vec<_> = files
+        .iter()
+        .map(|file| file.extension().unwrap().to_str().unwrap())
+        .collect();
+    assert_eq!(extensions, ["html", "svg"]);
+}
+
+#[test]
+fn asciicast() {
+    let path = std::env::temp_dir().join(format!("rustsnake-{}.cast", std::process::id()));
+    let mut session = Session::spawn(WIDTH, HEIGHT, &["--asciicast", path.to_str().unwrap()]);
+    session
+        .wait_for(TIMEOUT, |screen| screen.contains("Score: 0"))
+        .expect("the game did not start");
+    session.send(b"q");
+    assert!(session.wait(TIMEOUT).success());
+    let recording = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let mut lines = recording.lines();
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("{\"version\": 2, \"width\": 40, \"height\": 12, \"timestamp\": "));
+    let first_frame = lines.next().unwrap();
+    assert!(first_frame.starts_with("[0."));
+    assert!(first_frame.contains("Score: 0"));
+}
+
+#[test]
+fn render_replay_offline() {
+    let directory = std::env::temp_dir().join(format!("rustsnake-replay-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let args = ["--asciicast", "live.cast", "--save-replay", "game.replay"];
+    let mut session = Session::spawn_in(&directory, WIDTH, HEIGHT, &args);
+    session
+        .wait_for(TIMEOUT, |screen| screen.contains("Score: 0"))
+        .expect("the game did not start");
+    session.send(b"i");
+    session
+        .wait_for(TIMEOUT, |screen| screen.contains(" tick "))
+        .expect("the overlay was not shown");
+    session.send(b"e");
+    session
+        .wait_for(TIMEOUT, |screen| screen.contains("Saved rustsnake-"))
+        .expect("the screenshot was not saved");
+    let (_, y) = session.screen().find(SNAKE).unwrap();
+    session.send(b"s");
+    session
+        .wait_for(TIMEOUT, |screen| screen.find(SNAKE).unwrap().1 > y)
+        .expect("the snake did not turn");
+    session.send(b"q");
+    assert!(session.wait(TIMEOUT).success());
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rustsnake"))
+        .args(["--replay", "game.replay", "--asciicast", "offline.cast"])
+        .current_dir(&directory)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .starts_with("Final score: 0\n"));
+    let read = |name: &str| std::fs::read_to_string(directory.join(name)).unwrap();
+    let (replay, live, offline) = (read("game.replay"), read("live.cast"), read("offline.cast"));
+    std::fs::remove_dir_all(&directory).unwrap();
+    assert!(replay.starts_with("rustsnake-replay 1\n"));
+    assert!(replay.contains(" input 73\n"));
+    assert!(replay.contains(" notice  Saved rustsnake-"));
+    // Re-simulating the game renders exactly the frames shown while playing.
+    assert!(live.contains("Saved rustsnake-"));
+    assert_eq!(offline, live);
+}
+
+#[test]
+fn replay_requires_asciicast() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rustsnake"))
+        .args(["--replay", "game.replay"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(output.stderr, b"--replay requires --asciicast\n");
+}
+
+#[test]
+fn replay_seed() {
+    const SEED: &str = "0123-4567-89ab-cdef";
+    let food_position = |args: &[&str]| {
+        let mut session = Session::spawn(WIDTH, HEIGHT, args);
+        let screen = session
+            .wait_for(TIMEOUT, |screen| screen.find(FOOD).is_some())
+            .expect("no food was placed");
+        session.send(b"q");
+        assert!(session.wait(TIMEOUT).success());
+        if args.contains(&SEED) {
+            let seed_line = format!("Seed: {}", SEED);
+            assert!(session.screen().row(1).starts_with(&seed_line));
+        }
+        screen.find(FOOD).unwrap()
+    };
+
+    for rng in ["pcg32-fast", "xoshiro256starstar"] {
+        let first = food_position(&["--seed", SEED, "--rng", rng]);
+        assert_eq!(food_position(&["--seed", SEED, "--rng", rng]), first);
+    }
+}
+
+fn is_opposite(a: u8, b: u8) -> bool {
+    matches!(
+        (a, b),
+        (b'w', b's') | (b's', b'w') | (b'a', b'd') | (b'd', b'a')
+    )
+}
+
+#[test]
+fn eat_food() {
+    let mut session = Session::spawn(WIDTH, HEIGHT, &[]);
+    let mut heading = b'd';
+    loop {
+        let screen = session.screen();
+        if screen.contains("Score: 100") {
+            assert!(screen.contains("Speed: 5"));
+            break;
+        }
+        // Food is eaten one tick after reaching it, which may run the snake into the wall.
+        if screen.contains("Final score: 100") {
+            break;
+        }
+        if let (Some(head), Some(food)) = (screen.find(SNAKE), screen.find(FOOD)) {
+            // Steer towards the food, turning towards the center instead of reversing.
+            let vertical = match food.1.cmp(&head.1) {
+                std::cmp::Ordering::Less => b'w',
+                std::cmp::Ordering::Greater => b's',
+                std::cmp::Ordering::Equal if head.1 < HEIGHT / 2 => b's',
+                std::cmp::Ordering::Equal => b'w',
+            };
+            let horizontal = match food.0.cmp(&head.0) {
+                std::cmp::Ordering::Less => b'a',
+                std::cmp::Ordering::Greater => b'd',
+                std::cmp::Ordering::Equal if head.0 < WIDTH / 2 => b'd',
+                std::cmp::Ordering::Equal => b'a',
+            };
+            let mut key = if head.0 != food.0 {
+                horizontal
+            } else {
+                vertical
+            };
+            if is_opposite(key, heading) {
+                key = if head.0 != food.0 {
+                    vertical
+                } else {
+                    horizontal
+                };
+            }
+            if key != heading {
+                session.send(&[key]);
+                heading = key;
+            }
+        }
+        assert!(
+            session.wait_for_output(TIMEOUT),
+            "the game stopped rendering"
+        );
+    }
+}
@@ -0,0 +1,251 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use common::VirtualScreen;
+use rustsnake::frame_buffer::{Color, FrameBuffer, Pixel};
+use rustsnake::random::{PCG32Fast, RandomNumberEngineExt};
+use rustsnake::terminal::Capabilities;
+use rustsnake::types::{Dimensions, Position, Rect};
+
+const WIDTH: usize = 12;
+const HEIGHT: usize = 4;
+
+/// Renders frames into a virtual screen.
+struct Renderer {
+    frame_buffer: FrameBuffer,
+    screen: VirtualScreen,
+}
+
+const ALL: Capabilities = Capabilities {
+    erase_character: true,
+    repeat_character: true,
+    synchronized_output: false,
+};
+
+impl Renderer {
+    fn new() -> Self {
+        Self::with_capabilities(Capabilities::default())
+    }
+
+    fn with_capabilities(capabilities: Capabilities) -> Self {
+        Self {
+            frame_buffer: FrameBuffer::with_capabilities(
+                &Dimensions::new(WIDTH, HEIGHT),
+                capabilities,
+            ),
+            screen: VirtualScreen::new(WIDTH, HEIGHT),
+        }
+    }
+
+    /// Draws a frame, checks that the screen shows exactly what was drawn and returns the output.
+    fn render(&mut self, draw: impl FnOnce(&mut FrameBuffer)) -> Vec<u8> {
+        draw(&mut self.frame_buffer);
+        let mut output = Vec::new();
+        self.frame_buffer.swap_buffers_to(&mut output).unwrap();
+        // The front buffer holds the frame with the layers composed over it.
+        let expected: Vec<String> = self
+            .frame_buffer
+            .front_buffer()
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .filter(|pixel| !pixel.is_continuation())
+                    .map(|pixel| pixel.to_string())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(self.frame_buffer.swap_stats().bytes_written, output.len());
+        self.screen.feed(&output);
+        assert!(!self.screen.synchronized_update);
+        let rows: Vec<String> = (0..HEIGHT).map(|y| self.screen.row(y)).collect();
+        assert_eq!(
+            rows,
+            expected,
+            "output: {:?}",
+            String::from_utf8_lossy(&output)
+        );
+        // Blanks look the same in any color.
+        for (y, row) in self.frame_buffer.front_buffer().rows().enumerate() {
+            for (x, pixel) in row.iter().enumerate() {
+                if pixel.character != ' ' {
+                    let color = sgr_color(pixel.color);
+                    assert_eq!(self.screen.color(x, y), color, "color at ({}, {})", x, y);
+                }
+            }
+        }
+        output
+    }
+}
+
+/// Gets the SGR parameter selecting a color.
+fn sgr_color(color: Color) -> u8 {
+    let mut buffer = [0; 8];
+    let len = color.encode_ascii(&mut buffer);
+    std::str::from_utf8(&buffer[2..len - 1])
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+fn print(frame_buffer: &mut FrameBuffer, x: usize, y: usize, text: &str) {
+    frame_buffer
+        .canvas()
+        .print(Position::new(x, y), text, Color::Default);
+}
+
+#[test]
+fn wide_characters_keep_the_layout() {
+    let mut renderer = Renderer::new();
+    renderer.render(|frame_buffer| print(frame_buffer, 0, 0, "中a文b🐍c"));
+    assert_eq!(renderer.screen.row(0), "中a文b🐍c");
+    // Only the narrow character after the wide ones changes.
+    renderer.render(|frame_buffer| print(frame_buffer, 0, 0, "中a文b🐍d"));
+    assert_eq!(renderer.screen.row(0), "中a文b🐍d");
+}
+
+#[test]
+fn wide_characters_are_replaced() {
+    let mut renderer = Renderer::new();
+    renderer.render(|frame_buffer| print(frame_buffer, 0, 1, "中文字"));
+    renderer.render(|frame_buffer| print(frame_buffer, 0, 1, "ab文字"));
+    renderer.render(|frame_buffer| print(frame_buffer, 1, 1, "中文字"));
+    renderer.render(|frame_buffer| print(frame_buffer, 0, 1, "x中文字"));
+    renderer.render(|frame_buffer| print(frame_buffer, 1, 1, "y"));
+    assert_eq!(renderer.screen.row(1), " y");
+}
+
+#[test]
+fn wide_character_in_the_last_column() {
+    let mut renderer = Renderer::new();
+    renderer.render(|frame_buffer| print(frame_buffer, WIDTH - 3, 2, "a中"));
+    renderer.render(|frame_buffer| print(frame_buffer, WIDTH - 2, 2, "中"));
+    renderer.render(|frame_buffer| print(frame_buffer, WIDTH - 1, 2, "中"));
+    assert_eq!(renderer.screen.row(2), "");
+}
+
+#[test]
+fn combining_marks() {
+    let mut renderer = Renderer::with_capabilities(ALL);
+    renderer.render(|frame_buffer| print(frame_buffer, 0, 0, "e\u{301}e\u{301}e\u{301}中\u{301}"));
+    assert_eq!(renderer.screen.row(0), "e\u{301}e\u{301}e\u{301}中\u{301}");
+    renderer.render(|frame_buffer| print(frame_buffer, 0, 0, "eee中"));
+    assert_eq!(renderer.screen.row(0), "eee中");
+}
+
+#[test]
+fn runs_of_identical_cells() {
+    let mut renderer = Renderer::with_capabilities(ALL);
+    renderer.render(|frame_buffer| print(frame_buffer, 0, 0, "aaaaaaaaaaaa"));
+    renderer.render(|frame_buffer| print(frame_buffer, 0, 0, "aaaaaaaaaaaa"));
+    assert_eq!(renderer.frame_buffer.swap_stats().bytes_written, 0);
+    renderer.render(|frame_buffer| print(frame_buffer, 2, 0, "bbbbbbb"));
+    renderer.render(|frame_buffer| print(frame_buffer, 0, 0, "bbbbbbb"));
+    assert_eq!(renderer.screen.row(0), "bbbbbbb");
+}
+
+#[test]
+fn blanks_do_not_change_the_color() {
+    let mut renderer = Renderer::new();
+    let draw = |frame_buffer: &mut FrameBuffer, color| {
+        frame_buffer.canvas().fill(
+            &Rect::new(Position::new(0, 0), Dimensions::new(WIDTH, 2)),
+            Pixel::new(' ', color),
+        );
+        frame_buffer
+            .canvas()
+            .print(Position::new(0, 2), "x", Color::Red);
+    };
+    renderer.render(|frame_buffer| draw(frame_buffer, Color::Red));
+    let bytes_written = renderer.frame_buffer.swap_stats().bytes_written;
+    renderer.render(|frame_buffer| draw(frame_buffer, Color::Green));
+    assert_eq!(renderer.frame_buffer.swap_stats().bytes_written, 0);
+    assert!(bytes_written < 20);
+}
+
+#[test]
+fn synchronized_updates() {
+    let mut renderer = Renderer::with_capabilities(Capabilities {
+        synchronized_output: true,
+        ..Capabilities::default()
+    });
+    let output = renderer.render(|frame_buffer| print(frame_buffer, 0, 0, "abc"));
+    assert!(output.starts_with(b"\x1b[?2026h"));
+    assert!(output.ends_with(b"\x1b[?2026l"));
+    // Unchanged frames are not bracketed.
+    let output = renderer.render(|frame_buffer| print(frame_buffer, 0, 0, "abc"));
+    assert!(output.is_empty());
+}
+
+#[test]
+fn layers_are_kept_between_frames() {
+    let mut renderer = Renderer::with_capabilities(ALL);
+    let walls = renderer.frame_buffer.add_layer("walls", 0);
+    let overlay = renderer.frame_buffer.add_layer("overlay", 1);
+    renderer.frame_buffer.layer_mut(walls).canvas().fill(
+        &Rect::new(Position::new(0, 0), Dimensions::new(WIDTH, HEIGHT)),
+        Pixel::new('█', Color::Yellow),
+    );
+    renderer.frame_buffer.layer_mut(walls).canvas().fill(
+        &Rect::new(Position::new(1, 1), Dimensions::new(WIDTH - 2, HEIGHT - 2)),
+        Pixel::TRANSPARENT,
+    );
+    renderer.frame_buffer.layer_mut(overlay).canvas().print(
+        Position::new(2, 0),
+        " 中 ",
+        Color::White,
+    );
+    renderer.render(|frame_buffer| print(frame_buffer, 1, 1, "◉"));
+    assert_eq!(renderer.screen.row(0), "██ 中 ██████");
+    assert_eq!(renderer.screen.row(1), "█◉         █");
+    // Only the cells beneath the hidden overlay and the moving snake change.
+    renderer.frame_buffer.layer_mut(overlay).set_visible(false);
+    renderer.render(|frame_buffer| print(frame_buffer, 2, 1, "◉"));
+    assert_eq!(renderer.frame_buffer.swap_stats().dirty_cells, 6);
+    assert_eq!(renderer.screen.row(0), "████████████");
+    assert_eq!(renderer.screen.row(1), "█ ◉        █");
+}
+
+fn random_frames(capabilities: Capabilities) {
+    const CHARACTERS: [char; 8] = [' ', 'a', 'b', '中', '文', '🐍', '◉', '\u{301}'];
+    const COLORS: [Color; 3] = [Color::Default, Color::Red, Color::Green];
+    let mut renderer = Renderer::with_capabilities(capabilities);
+    let mut rng = PCG32Fast::new(None);
+    for _ in 0..1000 {
+        let mut runs = Vec::new();
+        for _ in 0..rng.gen_below(20) {
+            let position = Position::new(
+                rng.gen_below(WIDTH as u64) as usize,
+                rng.gen_below(HEIGHT as u64) as usize,
+            );
+            let character = *rng.choose(&CHARACTERS).unwrap();
+            let color = *rng.choose(&COLORS).unwrap();
+            let length = 1 + rng.gen_below(8) as usize;
+            runs.push((position, Pixel::new(character, color), length));
+        }
+        renderer.render(|frame_buffer| {
+            for (position, pixel, length) in runs {
+                for i in 0..length {
+                    let x = position.x + i * pixel.width as usize;
+                    if x < WIDTH {
+                        frame_buffer
+                            .back_buffer()
+                            .set_pixel(&Position::new(x, position.y), pixel);
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[test]
+fn random_frames_with_basic_capabilities() {
+    random_frames(Capabilities::default());
+}
+
+#[test]
+fn random_frames_with_all_capabilities() {
+    random_frames(ALL);
+}